@@ -39,6 +39,16 @@ pub enum AmmError {
     InsufficientBalance,
     #[msg("Zero balance.")]
     ZeroBalance,
+    #[msg("Trader is not on the pool allowlist.")]
+    TraderNotAllowed,
+    #[msg("Dynamic fee decay period must be positive.")]
+    InvalidDecayPeriod,
+    #[msg("Farm end time must be in the future and cannot be moved earlier.")]
+    InvalidEndTime,
+    #[msg("Pool mints must differ.")]
+    IdenticalMints,
+    #[msg("Reward vault cannot cover the claim; the farm needs a top up.")]
+    InsufficientRewards,
 }
 
 impl From<CurveError> for AmmError {
@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    errors::AmmError,
+    state::{AllowedTrader, Config},
+};
+
+#[derive(Accounts)]
+#[instruction(trader: Pubkey)]
+pub struct AllowTrader<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"trader", config.key().as_ref(), trader.as_ref()],
+        bump,
+        space = AllowedTrader::DISCRIMINATOR.len() + AllowedTrader::INIT_SPACE,
+    )]
+    pub allowed_trader: Account<'info, AllowedTrader>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> AllowTrader<'info> {
+    pub fn allow_trader(&mut self, trader: Pubkey, bumps: AllowTraderBumps) -> Result<()> {
+        let authority = self.config.authority.ok_or(AmmError::NoAuthoritySet)?;
+        require_keys_eq!(authority, self.authority.key(), AmmError::InvalidAuthority);
+
+        self.allowed_trader.set_inner(AllowedTrader {
+            config: self.config.key(),
+            trader,
+            bump: bumps.allowed_trader,
+        });
+
+        Ok(())
+    }
+}
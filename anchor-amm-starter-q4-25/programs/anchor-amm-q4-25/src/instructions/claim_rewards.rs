@@ -0,0 +1,86 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{transfer, Mint, Token, TokenAccount, Transfer},
+};
+
+use crate::{
+    errors::AmmError,
+    state::{Config, Farm, StakePosition},
+};
+
+#[derive(Accounts)]
+pub struct ClaimRewards<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+    pub reward_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        has_one = config,
+        has_one = reward_mint,
+        seeds = [b"farm", config.key().as_ref()],
+        bump = farm.bump,
+    )]
+    pub farm: Account<'info, Farm>,
+    #[account(
+        mut,
+        has_one = farm,
+        constraint = position.owner == user.key() @ AmmError::InvalidAuthority,
+        seeds = [b"position", farm.key().as_ref(), user.key().as_ref()],
+        bump = position.bump,
+    )]
+    pub position: Account<'info, StakePosition>,
+    #[account(
+        mut,
+        associated_token::mint = reward_mint,
+        associated_token::authority = farm,
+    )]
+    pub reward_vault: Account<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = reward_mint,
+        associated_token::authority = user,
+    )]
+    pub user_reward: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ClaimRewards<'info> {
+    pub fn claim_rewards(&mut self) -> Result<()> {
+        self.farm.update(Clock::get()?.unix_timestamp)?;
+        self.position.settle(self.farm.acc_reward_per_share)?;
+
+        let amount = self.position.pending;
+        require!(amount != 0, AmmError::ZeroBalance);
+        require!(
+            amount <= self.reward_vault.amount,
+            AmmError::InsufficientRewards
+        );
+        self.position.pending = 0;
+
+        let cpi_accounts = Transfer {
+            from: self.reward_vault.to_account_info(),
+            to: self.user_reward.to_account_info(),
+            authority: self.farm.to_account_info(),
+        };
+
+        let config_key = self.config.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[b"farm", config_key.as_ref(), &[self.farm.bump]]];
+
+        let ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            cpi_accounts,
+            signer_seeds,
+        );
+
+        transfer(ctx, amount)
+    }
+}
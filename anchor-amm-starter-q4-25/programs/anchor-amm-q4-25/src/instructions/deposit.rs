@@ -5,7 +5,10 @@ use anchor_spl::{
 };
 use constant_product_curve::ConstantProduct;
 
-use crate::{errors::AmmError, state::Config};
+use crate::{
+    errors::AmmError,
+    state::{AllowedTrader, Config},
+};
 
 #[derive(Accounts)]
 pub struct Deposit<'info> {
@@ -20,6 +23,11 @@ pub struct Deposit<'info> {
         bump = config.config_bump,
     )]
     pub config: Account<'info, Config>,
+    #[account(
+        seeds = [b"trader", config.key().as_ref(), user.key().as_ref()],
+        bump = allowed_trader.bump,
+    )]
+    pub allowed_trader: Option<Account<'info, AllowedTrader>>,
     #[account(
         mut,
         seeds = [b"lp", config.key().as_ref()],
@@ -71,6 +79,10 @@ impl<'info> Deposit<'info> {
     ) -> Result<()> {
         require!(self.config.locked == false, AmmError::PoolLocked);
         require!(amount != 0, AmmError::InvalidAmount);
+        require!(
+            !self.config.allowlist || self.allowed_trader.is_some(),
+            AmmError::TraderNotAllowed
+        );
 
         let (x, y) = match self.mint_lp.supply == 0
             && self.vault_x.amount == 0
@@ -0,0 +1,81 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+};
+
+use crate::{
+    errors::AmmError,
+    state::{Config, Farm},
+};
+
+#[derive(Accounts)]
+pub struct InitFarm<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        seeds = [b"lp", config.key().as_ref()],
+        bump = config.lp_bump,
+    )]
+    pub mint_lp: Account<'info, Mint>,
+    pub reward_mint: Account<'info, Mint>,
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"farm", config.key().as_ref()],
+        bump,
+        space = Farm::DISCRIMINATOR.len() + Farm::INIT_SPACE,
+    )]
+    pub farm: Account<'info, Farm>,
+    #[account(
+        init,
+        payer = authority,
+        associated_token::mint = reward_mint,
+        associated_token::authority = farm,
+    )]
+    pub reward_vault: Account<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = authority,
+        associated_token::mint = mint_lp,
+        associated_token::authority = farm,
+    )]
+    pub lp_vault: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitFarm<'info> {
+    pub fn init_farm(
+        &mut self,
+        emission_rate: u64,
+        end_time: i64,
+        bumps: InitFarmBumps,
+    ) -> Result<()> {
+        let authority = self.config.authority.ok_or(AmmError::NoAuthoritySet)?;
+        require_keys_eq!(authority, self.authority.key(), AmmError::InvalidAuthority);
+        require!(emission_rate != 0, AmmError::InvalidAmount);
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(end_time > now, AmmError::InvalidEndTime);
+
+        self.farm.set_inner(Farm {
+            config: self.config.key(),
+            reward_mint: self.reward_mint.key(),
+            emission_rate,
+            end_time,
+            last_update: now,
+            acc_reward_per_share: 0,
+            total_staked: 0,
+            bump: bumps.farm,
+        });
+
+        Ok(())
+    }
+}
@@ -4,7 +4,10 @@ use anchor_spl::{
     token::{Mint, Token, TokenAccount},
 };
 
-use crate::state::Config;
+use crate::{
+    errors::AmmError,
+    state::{Config, DynamicFee, DynamicFeeParams, PairIndex, PoolEntry},
+};
 
 #[derive(Accounts)]
 #[instruction(seed: u64)]
@@ -12,6 +15,9 @@ pub struct Initialize<'info> {
     #[account(mut)]
     pub initializer: Signer<'info>,
     pub mint_x: Account<'info, Mint>,
+    #[account(
+        constraint = mint_y.key() != mint_x.key() @ AmmError::IdenticalMints,
+    )]
     pub mint_y: Account<'info, Mint>,
     #[account(
         init,
@@ -44,6 +50,31 @@ pub struct Initialize<'info> {
         space = Config::DISCRIMINATOR.len() + Config::INIT_SPACE,
     )]
     pub config: Account<'info, Config>,
+    #[account(
+        init_if_needed,
+        payer = initializer,
+        seeds = [
+            b"pair",
+            mint_x.key().min(mint_y.key()).as_ref(),
+            mint_x.key().max(mint_y.key()).as_ref(),
+        ],
+        bump,
+        space = PairIndex::DISCRIMINATOR.len() + PairIndex::INIT_SPACE,
+    )]
+    pub pair_index: Account<'info, PairIndex>,
+    #[account(
+        init,
+        payer = initializer,
+        seeds = [
+            b"pair",
+            mint_x.key().min(mint_y.key()).as_ref(),
+            mint_x.key().max(mint_y.key()).as_ref(),
+            pair_index.pool_count.to_le_bytes().as_ref(),
+        ],
+        bump,
+        space = PoolEntry::DISCRIMINATOR.len() + PoolEntry::INIT_SPACE,
+    )]
+    pub pool_entry: Account<'info, PoolEntry>,
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
@@ -55,19 +86,56 @@ impl<'info> Initialize<'info> {
         seed: u64,
         fee: u16,
         authority: Option<Pubkey>,
+        allowlist: bool,
+        dynamic_fee: Option<DynamicFeeParams>,
         bumps: InitializeBumps,
     ) -> Result<()> {
+        // Only the authority can add traders, so an allowlisted pool needs one.
+        require!(!allowlist || authority.is_some(), AmmError::NoAuthoritySet);
+
+        let dynamic_fee = match dynamic_fee {
+            Some(params) => Some(DynamicFee::new(params, Clock::get()?.unix_timestamp)?),
+            None => None,
+        };
+
         self.config.set_inner(Config {
             seed,
             authority,
             mint_x: self.mint_x.key(),
             mint_y: self.mint_y.key(),
             fee,
+            dynamic_fee,
             locked: false,
+            allowlist,
             config_bump: bumps.config,
             lp_bump: bumps.mint_lp,
         });
 
+        self.register_pool(fee, &bumps)
+    }
+
+    // Records the new pool as the pair's next entry, creating the pair index on
+    // the first pool for the pair.
+    pub fn register_pool(&mut self, fee: u16, bumps: &InitializeBumps) -> Result<()> {
+        if self.pair_index.pool_count == 0 {
+            self.pair_index.mint_a = self.mint_x.key().min(self.mint_y.key());
+            self.pair_index.mint_b = self.mint_x.key().max(self.mint_y.key());
+            self.pair_index.bump = bumps.pair_index;
+        }
+
+        self.pool_entry.set_inner(PoolEntry {
+            index: self.pair_index.pool_count,
+            config: self.config.key(),
+            fee,
+            bump: bumps.pool_entry,
+        });
+
+        self.pair_index.pool_count = self
+            .pair_index
+            .pool_count
+            .checked_add(1)
+            .ok_or(AmmError::Overflow)?;
+
         Ok(())
     }
-}
\ No newline at end of file
+}
@@ -1,9 +1,23 @@
+pub mod allow_trader;
+pub mod claim_rewards;
 pub mod deposit;
+pub mod init_farm;
 pub mod initialize;
+pub mod revoke_trader;
+pub mod stake;
 pub mod swap;
+pub mod top_up_farm;
+pub mod unstake;
 pub mod withdraw;
 
+pub use allow_trader::*;
+pub use claim_rewards::*;
 pub use deposit::*;
+pub use init_farm::*;
 pub use initialize::*;
+pub use revoke_trader::*;
+pub use stake::*;
 pub use swap::*;
-pub use withdraw::*;
\ No newline at end of file
+pub use top_up_farm::*;
+pub use unstake::*;
+pub use withdraw::*;
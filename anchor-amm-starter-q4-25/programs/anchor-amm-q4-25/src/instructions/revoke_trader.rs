@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    errors::AmmError,
+    state::{AllowedTrader, Config},
+};
+
+#[derive(Accounts)]
+pub struct RevokeTrader<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        close = authority,
+        has_one = config,
+        seeds = [b"trader", config.key().as_ref(), allowed_trader.trader.as_ref()],
+        bump = allowed_trader.bump,
+    )]
+    pub allowed_trader: Account<'info, AllowedTrader>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> RevokeTrader<'info> {
+    pub fn revoke_trader(&mut self) -> Result<()> {
+        let authority = self.config.authority.ok_or(AmmError::NoAuthoritySet)?;
+        require_keys_eq!(authority, self.authority.key(), AmmError::InvalidAuthority);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,102 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{transfer, Mint, Token, TokenAccount, Transfer},
+};
+
+use crate::{
+    errors::AmmError,
+    state::{AllowedTrader, Config, Farm, StakePosition},
+};
+
+#[derive(Accounts)]
+pub struct Stake<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        seeds = [b"trader", config.key().as_ref(), user.key().as_ref()],
+        bump = allowed_trader.bump,
+    )]
+    pub allowed_trader: Option<Account<'info, AllowedTrader>>,
+    #[account(
+        seeds = [b"lp", config.key().as_ref()],
+        bump = config.lp_bump,
+    )]
+    pub mint_lp: Account<'info, Mint>,
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"farm", config.key().as_ref()],
+        bump = farm.bump,
+    )]
+    pub farm: Account<'info, Farm>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        seeds = [b"position", farm.key().as_ref(), user.key().as_ref()],
+        bump,
+        space = StakePosition::DISCRIMINATOR.len() + StakePosition::INIT_SPACE,
+    )]
+    pub position: Account<'info, StakePosition>,
+    #[account(
+        mut,
+        associated_token::mint = mint_lp,
+        associated_token::authority = user,
+    )]
+    pub user_lp: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint_lp,
+        associated_token::authority = farm,
+    )]
+    pub lp_vault: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> Stake<'info> {
+    pub fn stake(&mut self, amount: u64, bumps: StakeBumps) -> Result<()> {
+        require!(amount != 0, AmmError::InvalidAmount);
+        require!(
+            !self.config.allowlist || self.allowed_trader.is_some(),
+            AmmError::TraderNotAllowed
+        );
+
+        if self.position.owner == Pubkey::default() {
+            self.position.farm = self.farm.key();
+            self.position.owner = self.user.key();
+            self.position.bump = bumps.position;
+        }
+
+        self.farm.update(Clock::get()?.unix_timestamp)?;
+        self.position.settle(self.farm.acc_reward_per_share)?;
+
+        self.position.amount = self
+            .position
+            .amount
+            .checked_add(amount)
+            .ok_or(AmmError::Overflow)?;
+        self.farm.total_staked = self
+            .farm
+            .total_staked
+            .checked_add(amount)
+            .ok_or(AmmError::Overflow)?;
+        self.position.reset_debt(self.farm.acc_reward_per_share)?;
+
+        let cpi_accounts = Transfer {
+            from: self.user_lp.to_account_info(),
+            to: self.lp_vault.to_account_info(),
+            authority: self.user.to_account_info(),
+        };
+
+        let ctx = CpiContext::new(self.token_program.to_account_info(), cpi_accounts);
+
+        transfer(ctx, amount)
+    }
+}
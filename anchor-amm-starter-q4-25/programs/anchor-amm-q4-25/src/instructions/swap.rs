@@ -5,7 +5,10 @@ use anchor_spl::{
 };
 use constant_product_curve::{ConstantProduct, LiquidityPair};
 
-use crate::{errors::AmmError, state::Config};
+use crate::{
+    errors::AmmError,
+    state::{AllowedTrader, Config},
+};
 
 #[derive(Accounts)]
 pub struct Swap<'info> {
@@ -21,6 +24,12 @@ pub mint_y:Account<'info,Mint>,
  )]
  pub config:Account<'info,Config>,
 
+    #[account(
+        seeds = [b"trader", config.key().as_ref(), user.key().as_ref()],
+        bump = allowed_trader.bump,
+    )]
+    pub allowed_trader: Option<Account<'info, AllowedTrader>>,
+
      #[account(
         mut,
         seeds = [b"lp", config.key().as_ref()],
@@ -69,8 +78,11 @@ impl<'info> Swap<'info> {
      require!(self.config.locked==false,AmmError::PoolLocked);
      require!(self.mint_lp.supply>0,AmmError::NoLiquidityInPool);
      require!(amount != 0, AmmError::InvalidAmount);
+     require!(!self.config.allowlist || self.allowed_trader.is_some(), AmmError::TraderNotAllowed);
 
-     let mut c: ConstantProduct=ConstantProduct::init(self.vault_x.amount,self.vault_y.amount,self.mint_lp.supply,self.config.fee,Some(6)).unwrap();
+     let fee = self.effective_fee()?;
+
+     let mut c: ConstantProduct=ConstantProduct::init(self.vault_x.amount,self.vault_y.amount,self.mint_lp.supply,fee,Some(6)).unwrap();
 
 
     let swap_res=match is_x {
@@ -82,9 +94,90 @@ impl<'info> Swap<'info> {
      self.deposit_tokens(is_x, swap_res.deposit)?;
      self.withdraw_tokens(is_x, swap_res.withdraw)?;
 
-     Ok(())
+     self.update_reference_price()
 }
 
+    pub fn swap_exact_out(&mut self, is_x: bool, amount_out: u64, max_amount_in: u64) -> Result<()> {
+        require!(!self.config.locked, AmmError::PoolLocked);
+        require!(self.mint_lp.supply > 0, AmmError::NoLiquidityInPool);
+        require!(amount_out != 0, AmmError::InvalidAmount);
+        require!(
+            !self.config.allowlist || self.allowed_trader.is_some(),
+            AmmError::TraderNotAllowed
+        );
+
+        let (reserve_in, reserve_out) = match is_x {
+            true => (self.vault_x.amount, self.vault_y.amount),
+            false => (self.vault_y.amount, self.vault_x.amount),
+        };
+
+        let fee = self.effective_fee()?;
+        let amount_in = Self::amount_in_for_exact_out(reserve_in, reserve_out, amount_out, fee)?;
+
+        require!(amount_in <= max_amount_in, AmmError::SlippageExceeded);
+
+        self.deposit_tokens(is_x, amount_in)?;
+        self.withdraw_tokens(is_x, amount_out)?;
+
+        self.update_reference_price()
+    }
+
+    // Fee for this swap: the static pool fee, or the volatility-adjusted fee when the
+    // pool runs in dynamic-fee mode. The first swap seeds the reference price.
+    fn effective_fee(&mut self) -> Result<u16> {
+        let now = Clock::get()?.unix_timestamp;
+
+        match self.config.dynamic_fee.as_mut() {
+            Some(dynamic_fee) => {
+                if dynamic_fee.reference_price == 0 {
+                    dynamic_fee.record_price(self.vault_x.amount, self.vault_y.amount, now)?;
+                }
+                Ok(dynamic_fee.current_fee(now))
+            }
+            None => Ok(self.config.fee),
+        }
+    }
+
+    fn update_reference_price(&mut self) -> Result<()> {
+        if self.config.dynamic_fee.is_none() {
+            return Ok(());
+        }
+
+        self.vault_x.reload()?;
+        self.vault_y.reload()?;
+
+        let now = Clock::get()?.unix_timestamp;
+        if let Some(dynamic_fee) = self.config.dynamic_fee.as_mut() {
+            dynamic_fee.record_price(self.vault_x.amount, self.vault_y.amount, now)?;
+        }
+
+        Ok(())
+    }
+
+    // Solves x * y = k for the input needed to pull `amount_out` from the pool and
+    // then grosses it up by the fee. Both divisions round up, in the pool's favour.
+    fn amount_in_for_exact_out(
+        reserve_in: u64,
+        reserve_out: u64,
+        amount_out: u64,
+        fee: u16,
+    ) -> Result<u64> {
+        require!(fee < 10_000, AmmError::InvalidFee);
+        require!(amount_out < reserve_out, AmmError::InsufficientBalance);
+
+        let amount_in_after_fee = (reserve_in as u128)
+            .checked_mul(amount_out as u128)
+            .ok_or(AmmError::Overflow)?
+            .div_ceil((reserve_out - amount_out) as u128);
+
+        let amount_in = amount_in_after_fee
+            .checked_mul(10_000)
+            .ok_or(AmmError::Overflow)?
+            .div_ceil((10_000 - fee) as u128);
+
+        u64::try_from(amount_in).map_err(|_| AmmError::Overflow.into())
+    }
+
     pub fn deposit_tokens(&mut self, is_x: bool, amount: u64) -> Result<()> {
 
         let (from,to)=match is_x {
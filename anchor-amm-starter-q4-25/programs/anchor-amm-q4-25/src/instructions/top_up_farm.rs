@@ -0,0 +1,80 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{transfer, Mint, Token, TokenAccount, Transfer};
+
+use crate::{
+    errors::AmmError,
+    state::{Config, Farm},
+};
+
+#[derive(Accounts)]
+pub struct TopUpFarm<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+    pub reward_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        has_one = config,
+        has_one = reward_mint,
+        seeds = [b"farm", config.key().as_ref()],
+        bump = farm.bump,
+    )]
+    pub farm: Account<'info, Farm>,
+    #[account(
+        mut,
+        associated_token::mint = reward_mint,
+        associated_token::authority = farm,
+    )]
+    pub reward_vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = reward_mint,
+        associated_token::authority = authority,
+    )]
+    pub authority_reward: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> TopUpFarm<'info> {
+    pub fn top_up_farm(&mut self, amount: u64, end_time: i64) -> Result<()> {
+        let authority = self.config.authority.ok_or(AmmError::NoAuthoritySet)?;
+        require_keys_eq!(authority, self.authority.key(), AmmError::InvalidAuthority);
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            end_time >= self.farm.end_time && end_time > now,
+            AmmError::InvalidEndTime
+        );
+
+        // Settle emissions under the old schedule. If the farm had already ended,
+        // the gap until now earns nothing once the end time is extended. Time
+        // the farm sat empty was already added to its end, so the new end time
+        // extends the schedule rather than replacing it.
+        let extension = end_time - self.farm.end_time;
+        self.farm.update(now)?;
+        self.farm.last_update = now;
+        self.farm.end_time = self
+            .farm
+            .end_time
+            .checked_add(extension)
+            .ok_or(AmmError::Overflow)?;
+
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let cpi_accounts = Transfer {
+            from: self.authority_reward.to_account_info(),
+            to: self.reward_vault.to_account_info(),
+            authority: self.authority.to_account_info(),
+        };
+
+        let ctx = CpiContext::new(self.token_program.to_account_info(), cpi_accounts);
+
+        transfer(ctx, amount)
+    }
+}
@@ -0,0 +1,85 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{transfer, Mint, Token, TokenAccount, Transfer};
+
+use crate::{
+    errors::AmmError,
+    state::{Config, Farm, StakePosition},
+};
+
+#[derive(Accounts)]
+pub struct Unstake<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        seeds = [b"lp", config.key().as_ref()],
+        bump = config.lp_bump,
+    )]
+    pub mint_lp: Account<'info, Mint>,
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"farm", config.key().as_ref()],
+        bump = farm.bump,
+    )]
+    pub farm: Account<'info, Farm>,
+    #[account(
+        mut,
+        has_one = farm,
+        constraint = position.owner == user.key() @ AmmError::InvalidAuthority,
+        seeds = [b"position", farm.key().as_ref(), user.key().as_ref()],
+        bump = position.bump,
+    )]
+    pub position: Account<'info, StakePosition>,
+    #[account(
+        mut,
+        associated_token::mint = mint_lp,
+        associated_token::authority = user,
+    )]
+    pub user_lp: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint_lp,
+        associated_token::authority = farm,
+    )]
+    pub lp_vault: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> Unstake<'info> {
+    pub fn unstake(&mut self, amount: u64) -> Result<()> {
+        require!(amount != 0, AmmError::InvalidAmount);
+        require!(
+            amount <= self.position.amount,
+            AmmError::InsufficientBalance
+        );
+
+        self.farm.update(Clock::get()?.unix_timestamp)?;
+        self.position.settle(self.farm.acc_reward_per_share)?;
+
+        self.position.amount -= amount;
+        self.farm.total_staked -= amount;
+        self.position.reset_debt(self.farm.acc_reward_per_share)?;
+
+        let cpi_accounts = Transfer {
+            from: self.lp_vault.to_account_info(),
+            to: self.user_lp.to_account_info(),
+            authority: self.farm.to_account_info(),
+        };
+
+        let config_key = self.config.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[b"farm", config_key.as_ref(), &[self.farm.bump]]];
+
+        let ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            cpi_accounts,
+            signer_seeds,
+        );
+
+        transfer(ctx, amount)
+    }
+}
@@ -5,7 +5,10 @@ use anchor_spl::{
 };
 use constant_product_curve::ConstantProduct;
 
-use crate::{errors::AmmError, state::Config};
+use crate::{
+    errors::AmmError,
+    state::{AllowedTrader, Config},
+};
 
 #[derive(Accounts)]
 pub struct Withdraw<'info> {
@@ -21,6 +24,12 @@ pub struct Withdraw<'info> {
  )]
     pub config: Account<'info, Config>,
 
+    #[account(
+        seeds = [b"trader", config.key().as_ref(), user.key().as_ref()],
+        bump = allowed_trader.bump,
+    )]
+    pub allowed_trader: Option<Account<'info, AllowedTrader>>,
+
     #[account(
         mut,
         seeds = [b"lp", config.key().as_ref()],
@@ -77,6 +86,10 @@ impl<'info> Withdraw<'info> {
 
         require!(self.config.locked==false,AmmError::PoolLocked);
         require!(amount!=0,AmmError::InvalidAmount);
+        require!(
+            !self.config.allowlist || self.allowed_trader.is_some(),
+            AmmError::TraderNotAllowed
+        );
         require!(self.mint_lp.supply!=0,AmmError::InsufficientBalance);
 
        let mut c=ConstantProduct::init(self.vault_x.amount, self.vault_y.amount,self.mint_lp.supply,self.config.fee,Some(6)).unwrap();
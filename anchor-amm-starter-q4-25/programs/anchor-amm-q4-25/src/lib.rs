@@ -5,6 +5,7 @@ mod instructions;
 mod state;
 
 use instructions::*;
+use state::DynamicFeeParams;
 declare_id!("9FVkz5L9LZix4zXYmqJLzKBwRpm9aJ539J26UsZSrLWN");
 
 #[program]
@@ -16,8 +17,19 @@ pub mod anchor_amm_q4_25 {
         seed: u64,
         fee: u16,
         authority: Option<Pubkey>,
+        allowlist: bool,
+        dynamic_fee: Option<DynamicFeeParams>,
     ) -> Result<()> {
-        ctx.accounts.init(seed, fee, authority, ctx.bumps)
+        ctx.accounts
+            .init(seed, fee, authority, allowlist, dynamic_fee, ctx.bumps)
+    }
+
+    pub fn allow_trader(ctx: Context<AllowTrader>, trader: Pubkey) -> Result<()> {
+        ctx.accounts.allow_trader(trader, ctx.bumps)
+    }
+
+    pub fn revoke_trader(ctx: Context<RevokeTrader>) -> Result<()> {
+        ctx.accounts.revoke_trader()
     }
 
     pub fn deposit(ctx: Context<Deposit>, amount: u64, max_x: u64, max_y: u64) -> Result<()> {
@@ -31,4 +43,33 @@ pub mod anchor_amm_q4_25 {
     pub fn swap(ctx: Context<Swap>, is_x: bool, amount_in: u64, min_amount_out: u64) -> Result<()> {
         ctx.accounts.swap(is_x, amount_in, min_amount_out)
     }
+
+    pub fn swap_exact_out(
+        ctx: Context<Swap>,
+        is_x: bool,
+        amount_out: u64,
+        max_amount_in: u64,
+    ) -> Result<()> {
+        ctx.accounts.swap_exact_out(is_x, amount_out, max_amount_in)
+    }
+
+    pub fn init_farm(ctx: Context<InitFarm>, emission_rate: u64, end_time: i64) -> Result<()> {
+        ctx.accounts.init_farm(emission_rate, end_time, ctx.bumps)
+    }
+
+    pub fn top_up_farm(ctx: Context<TopUpFarm>, amount: u64, end_time: i64) -> Result<()> {
+        ctx.accounts.top_up_farm(amount, end_time)
+    }
+
+    pub fn stake(ctx: Context<Stake>, amount: u64) -> Result<()> {
+        ctx.accounts.stake(amount, ctx.bumps)
+    }
+
+    pub fn unstake(ctx: Context<Unstake>, amount: u64) -> Result<()> {
+        ctx.accounts.unstake(amount)
+    }
+
+    pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
+        ctx.accounts.claim_rewards()
+    }
 }
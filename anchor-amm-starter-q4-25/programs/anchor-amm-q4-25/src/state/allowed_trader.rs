@@ -0,0 +1,9 @@
+use anchor_lang::prelude::*;
+
+#[account]
+#[derive(InitSpace)]
+pub struct AllowedTrader {
+    pub config: Pubkey, // Pool this entry belongs to
+    pub trader: Pubkey, // Wallet allowed to trade on the pool
+    pub bump: u8,       // Bump seed for the allowlist entry
+}
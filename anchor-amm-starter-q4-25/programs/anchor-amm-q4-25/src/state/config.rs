@@ -1,5 +1,7 @@
 use anchor_lang::prelude::*;
 
+use crate::state::DynamicFee;
+
 #[account]
 #[derive(InitSpace)]
 pub struct Config {
@@ -8,7 +10,10 @@ pub struct Config {
     pub mint_x: Pubkey,            // Token X
     pub mint_y: Pubkey,            // Token Y
     pub fee: u16,                  // Swap fee in basis points
+    pub dynamic_fee: Option<DynamicFee>, // Volatility-based fee; `fee` is used when unset
     pub locked: bool,              // If the pool is locked
+    pub allowlist: bool,           // If only wallets with an AllowedTrader entry can swap, deposit or stake;
+                                   // withdraw, unstake and claims stay open so removed traders can exit
     pub config_bump: u8,           // Bump seed for the config account
     pub lp_bump: u8,               // Bump seed for the LP token
 }
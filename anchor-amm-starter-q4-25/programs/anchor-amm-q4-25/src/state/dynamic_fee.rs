@@ -0,0 +1,89 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::AmmError;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct DynamicFeeParams {
+    pub min_fee: u16,      // Fee floor in basis points
+    pub max_fee: u16,      // Fee ceiling in basis points
+    pub decay_period: i64, // Seconds for recent volatility to decay back to zero
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct DynamicFee {
+    pub min_fee: u16,          // Fee floor in basis points
+    pub max_fee: u16,          // Fee ceiling in basis points
+    pub decay_period: i64,     // Seconds for recent volatility to decay back to zero
+    pub volatility: u64,       // Recent price movement in basis points
+    pub reference_price: u128, // Price of X in Y, scaled by PRICE_SCALE, after the last swap
+    pub last_update: i64,      // Timestamp of the last swap
+}
+
+impl DynamicFee {
+    pub const PRICE_SCALE: u128 = 1_000_000_000_000;
+    pub const MAX_VOLATILITY: u64 = 10_000;
+
+    pub fn new(params: DynamicFeeParams, now: i64) -> Result<Self> {
+        require!(
+            params.min_fee <= params.max_fee && params.max_fee < 10_000,
+            AmmError::InvalidFee
+        );
+        require!(params.decay_period > 0, AmmError::InvalidDecayPeriod);
+
+        Ok(Self {
+            min_fee: params.min_fee,
+            max_fee: params.max_fee,
+            decay_period: params.decay_period,
+            volatility: 0,
+            reference_price: 0,
+            last_update: now,
+        })
+    }
+
+    // Volatility decays linearly to zero over `decay_period` seconds since the last swap.
+    pub fn decayed_volatility(&self, now: i64) -> u64 {
+        let elapsed = now.saturating_sub(self.last_update).max(0);
+        if elapsed >= self.decay_period {
+            return 0;
+        }
+
+        (self.volatility as u128 * (self.decay_period - elapsed) as u128
+            / self.decay_period as u128) as u64
+    }
+
+    pub fn current_fee(&self, now: i64) -> u16 {
+        (self.min_fee as u64)
+            .saturating_add(self.decayed_volatility(now))
+            .min(self.max_fee as u64) as u16
+    }
+
+    // Adds the move away from the reference price to the decayed volatility and
+    // makes the new price the reference for the next swap.
+    pub fn record_price(&mut self, reserve_x: u64, reserve_y: u64, now: i64) -> Result<()> {
+        require!(reserve_x != 0, AmmError::ZeroBalance);
+
+        let price = (reserve_y as u128)
+            .checked_mul(Self::PRICE_SCALE)
+            .ok_or(AmmError::Overflow)?
+            / reserve_x as u128;
+
+        if self.reference_price != 0 {
+            let movement = price
+                .abs_diff(self.reference_price)
+                .checked_mul(10_000)
+                .ok_or(AmmError::Overflow)?
+                .checked_div(self.reference_price)
+                .ok_or(AmmError::ZeroBalance)?;
+
+            self.volatility = self
+                .decayed_volatility(now)
+                .saturating_add(movement.min(Self::MAX_VOLATILITY as u128) as u64)
+                .min(Self::MAX_VOLATILITY);
+        }
+
+        self.reference_price = price;
+        self.last_update = now;
+
+        Ok(())
+    }
+}
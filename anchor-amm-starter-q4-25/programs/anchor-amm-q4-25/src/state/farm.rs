@@ -0,0 +1,57 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::AmmError;
+
+#[account]
+#[derive(InitSpace)]
+pub struct Farm {
+    pub config: Pubkey,             // Pool whose LP tokens are staked
+    pub reward_mint: Pubkey,        // Token paid out as rewards
+    pub emission_rate: u64,         // Reward tokens emitted per second across all stakers
+    pub end_time: i64,              // Last accruing second; moves out while none staked
+    pub last_update: i64,           // Timestamp rewards were last accrued up to
+    pub acc_reward_per_share: u128, // Rewards per staked LP token, scaled by ACC_PRECISION
+    pub total_staked: u64,          // LP tokens currently staked in the farm
+    pub bump: u8,                   // Bump seed for the farm account
+}
+
+impl Farm {
+    pub const ACC_PRECISION: u128 = 1_000_000_000_000;
+
+    // Accrues emissions since the last update into the reward-per-share accumulator.
+    pub fn update(&mut self, now: i64) -> Result<()> {
+        let until = now.min(self.end_time);
+        if until <= self.last_update {
+            return Ok(());
+        }
+
+        // Nobody can earn emissions while the farm is empty, so push the rest
+        // of the schedule back instead of leaving them stranded in the vault.
+        if self.total_staked == 0 {
+            self.end_time = self
+                .end_time
+                .checked_add(now - self.last_update)
+                .ok_or(AmmError::Overflow)?;
+            self.last_update = now;
+            return Ok(());
+        }
+
+        let rewards = ((until - self.last_update) as u128)
+            .checked_mul(self.emission_rate as u128)
+            .ok_or(AmmError::Overflow)?;
+
+        let per_share = rewards
+            .checked_mul(Self::ACC_PRECISION)
+            .ok_or(AmmError::Overflow)?
+            / self.total_staked as u128;
+
+        self.acc_reward_per_share = self
+            .acc_reward_per_share
+            .checked_add(per_share)
+            .ok_or(AmmError::Overflow)?;
+
+        self.last_update = until;
+
+        Ok(())
+    }
+}
@@ -1,3 +1,13 @@
+pub mod allowed_trader;
 pub mod config;
+pub mod dynamic_fee;
+pub mod farm;
+pub mod pair_index;
+pub mod stake_position;
 
+pub use allowed_trader::*;
 pub use config::*;
+pub use dynamic_fee::*;
+pub use farm::*;
+pub use pair_index::*;
+pub use stake_position::*;
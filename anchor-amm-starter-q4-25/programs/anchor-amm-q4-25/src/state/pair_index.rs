@@ -0,0 +1,21 @@
+use anchor_lang::prelude::*;
+
+#[account]
+#[derive(InitSpace)]
+pub struct PairIndex {
+    pub mint_a: Pubkey,   // Lower of the two pool mints
+    pub mint_b: Pubkey,   // Higher of the two pool mints
+    pub pool_count: u64,  // Number of PoolEntry accounts registered for the pair
+    pub bump: u8,         // Bump seed for the pair index
+}
+
+// One per pool, at ["pair", mint_a, mint_b, index], so registering a pool
+// never has to load or grow the pools registered before it.
+#[account]
+#[derive(InitSpace)]
+pub struct PoolEntry {
+    pub index: u64,     // Position of the pool in the pair's creation order
+    pub config: Pubkey, // Pool config account
+    pub fee: u16,       // Swap fee in basis points the pool was created with
+    pub bump: u8,       // Bump seed for the pool entry
+}
@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::AmmError, state::Farm};
+
+#[account]
+#[derive(InitSpace)]
+pub struct StakePosition {
+    pub farm: Pubkey,      // Farm this position stakes into
+    pub owner: Pubkey,     // Wallet that owns the staked LP tokens
+    pub amount: u64,       // LP tokens staked
+    pub reward_debt: u128, // Accumulator value already accounted for, scaled by ACC_PRECISION
+    pub pending: u64,      // Rewards accrued but not yet claimed
+    pub bump: u8,          // Bump seed for the position account
+}
+
+impl StakePosition {
+    // Moves everything earned since the last settlement into `pending`.
+    // Must run after `Farm::update` and before `amount` changes.
+    pub fn settle(&mut self, acc_reward_per_share: u128) -> Result<()> {
+        let accrued = (self.amount as u128)
+            .checked_mul(acc_reward_per_share)
+            .ok_or(AmmError::Overflow)?;
+        let earned = (accrued - self.reward_debt) / Farm::ACC_PRECISION;
+
+        self.pending = self
+            .pending
+            .checked_add(u64::try_from(earned).map_err(|_| AmmError::Overflow)?)
+            .ok_or(AmmError::Overflow)?;
+        self.reward_debt = accrued;
+
+        Ok(())
+    }
+
+    // Resets the debt after `amount` changes so past rewards are not paid twice.
+    pub fn reset_debt(&mut self, acc_reward_per_share: u128) -> Result<()> {
+        self.reward_debt = (self.amount as u128)
+            .checked_mul(acc_reward_per_share)
+            .ok_or(AmmError::Overflow)?;
+
+        Ok(())
+    }
+}
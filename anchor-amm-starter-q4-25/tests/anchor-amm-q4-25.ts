@@ -32,6 +32,8 @@ describe("anchor-amm-q4-25", () => {
   let vaultX: anchor.web3.PublicKey;
   let vaultY: anchor.web3.PublicKey;
   let configPda: anchor.web3.PublicKey;
+  let pairIndex: anchor.web3.PublicKey;
+  let poolEntry: (index: number) => anchor.web3.PublicKey;
   let lpBump: number;
   let configBump: number;
 
@@ -134,6 +136,17 @@ describe("anchor-amm-q4-25", () => {
       program.programId
     );
 
+    const [mintA, mintB] =
+      Buffer.compare(mintX.toBuffer(), mintY.toBuffer()) < 0 ? [mintX, mintY] : [mintY, mintX];
+    [pairIndex] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("pair"), mintA.toBuffer(), mintB.toBuffer()],
+      program.programId
+    );
+    poolEntry = (index: number) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("pair"), mintA.toBuffer(), mintB.toBuffer(), new anchor.BN(index).toArrayLike(Buffer, "le", 8)],
+        program.programId
+      )[0];
 
 
    
@@ -148,7 +161,7 @@ describe("anchor-amm-q4-25", () => {
     vaultY = getAssociatedTokenAddressSync(mintY, configPda,true);
     
     const tx=await program.methods
-      .initialize(seed, fee, user)
+      .initialize(seed, fee, user, false, null)
       .accountsStrict({
         initializer: user,
         mintX: mintX,
@@ -157,6 +170,8 @@ describe("anchor-amm-q4-25", () => {
         vaultX: vaultX,
         vaultY: vaultY,
         config: configPda,
+        pairIndex,
+        poolEntry: poolEntry(0),
         tokenProgram: TOKEN_PROGRAM_ID,
         associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
@@ -171,6 +186,12 @@ describe("anchor-amm-q4-25", () => {
     expect(configAccount.configBump).to.equal(configBump);
     expect(configAccount.lpBump).to.equal(lpBump);
     expect(configAccount.locked).to.equal(false);
+
+    const pairIndexAccount = await program.account.pairIndex.fetch(pairIndex);
+    expect(pairIndexAccount.poolCount.toNumber()).to.equal(1);
+    const entry = await program.account.poolEntry.fetch(poolEntry(0));
+    expect(entry.config.toBase58()).to.equal(configPda.toBase58());
+    expect(entry.fee).to.equal(fee);
     console.log("Your transaction signature", tx);
   });
 
@@ -194,6 +215,7 @@ describe("anchor-amm-q4-25", () => {
       mintX,
       mintY,
       config: configPda,
+      allowedTrader: null,
       mintLp,
       vaultX,
       vaultY,
@@ -235,6 +257,7 @@ describe("anchor-amm-q4-25", () => {
       mintX,
       mintY,
       config: configPda,
+      allowedTrader: null,
       mintLp,
       vaultX,
       vaultY,
@@ -276,6 +299,7 @@ describe("anchor-amm-q4-25", () => {
       mintX,
       mintY,
       config: configPda,
+      allowedTrader: null,
       mintLp,
       vaultX,
       vaultY,
@@ -301,6 +325,78 @@ describe("anchor-amm-q4-25", () => {
   
   });
 
+  it("swapping X for exactly 5 Y tokens", async () => {
+    const yTokenOut = new anchor.BN(5_000_000);
+    const maxXToken = new anchor.BN(10_000_000);
+
+    const beforeUserAtaXAccount = await getAccount(provider.connection, userAtaX);
+    const beforeUserAtaYAccount = await getAccount(provider.connection, userAtaY);
+    const reserveX = (await getAccount(provider.connection, vaultX)).amount;
+    const reserveY = (await getAccount(provider.connection, vaultY)).amount;
+
+    const swapAccounts = {
+      user,
+      mintX,
+      mintY,
+      config: configPda,
+      allowedTrader: null,
+      mintLp,
+      vaultX,
+      vaultY,
+      userX: userAtaX,
+      userY: userAtaY,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    };
+
+    await program.methods.swapExactOut(true, yTokenOut, maxXToken).accountsStrict(swapAccounts).rpc();
+
+    const afterUserAtaXAccount = await getAccount(provider.connection, userAtaX);
+    const afterUserAtaYAccount = await getAccount(provider.connection, userAtaY);
+
+    const spentX = beforeUserAtaXAccount.amount - afterUserAtaXAccount.amount;
+    const receivedY = afterUserAtaYAccount.amount - beforeUserAtaYAccount.amount;
+
+    // x * y = k solved for the input, then grossed up by the fee, both rounded up
+    const ceilDiv = (a: bigint, b: bigint) => (a + b - BigInt(1)) / b;
+    const out = BigInt(yTokenOut.toString());
+    const amountInAfterFee = ceilDiv(reserveX * out, reserveY - out);
+    const expectedX = ceilDiv(amountInAfterFee * BigInt(10_000), BigInt(10_000 - fee));
+
+    expect(receivedY.toString()).to.equal(yTokenOut.toString());
+    expect(spentX.toString()).to.equal(expectedX.toString());
+    assert(spentX <= BigInt(maxXToken.toString()), "user's x spend should respect max_amount_in");
+  });
+
+  it("rejects an exact-out swap above max_amount_in", async () => {
+    const swapAccounts = {
+      user,
+      mintX,
+      mintY,
+      config: configPda,
+      allowedTrader: null,
+      mintLp,
+      vaultX,
+      vaultY,
+      userX: userAtaX,
+      userY: userAtaY,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    };
+
+    try {
+      await program.methods
+        .swapExactOut(true, new anchor.BN(5_000_000), new anchor.BN(1))
+        .accountsStrict(swapAccounts)
+        .rpc();
+      assert.fail("swap should have failed");
+    } catch (err) {
+      expect((err as anchor.AnchorError).error.errorCode.code).to.equal("SlippageExceeded");
+    }
+  });
+
   //withdraw---------
   it("withdrawing half liquidity from pool", async () => {
    
@@ -317,6 +413,7 @@ describe("anchor-amm-q4-25", () => {
       mintX,
       mintY,
       config: configPda,
+      allowedTrader: null,
       mintLp,
       userLp,
       vaultX,
@@ -343,4 +440,331 @@ describe("anchor-amm-q4-25", () => {
     assert(afterUserLpTokens.amount < beforeUserLpTokens.amount, "user's lp token should decrease");
 
   });
-});
\ No newline at end of file
+  //allowlist---------
+  it("allowlisted pool rejects unlisted traders until allowed", async () => {
+    const allowSeed = new anchor.BN(5678);
+    const [allowConfig] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("config"), allowSeed.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const [allowMintLp] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("lp"), allowConfig.toBuffer()],
+      program.programId
+    );
+    const [allowedTrader] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("trader"), allowConfig.toBuffer(), user.toBuffer()],
+      program.programId
+    );
+    const allowVaultX = getAssociatedTokenAddressSync(mintX, allowConfig, true);
+    const allowVaultY = getAssociatedTokenAddressSync(mintY, allowConfig, true);
+    const allowUserLp = getAssociatedTokenAddressSync(allowMintLp, user);
+
+    const initAccounts = {
+      initializer: user,
+      mintX,
+      mintY,
+      mintLp: allowMintLp,
+      vaultX: allowVaultX,
+      vaultY: allowVaultY,
+      config: allowConfig,
+      pairIndex,
+      poolEntry: poolEntry(1),
+      tokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    };
+
+    // Without an authority nobody could ever be allowed to trade
+    try {
+      await program.methods.initialize(allowSeed, fee, null, true, null).accountsStrict(initAccounts).rpc();
+      assert.fail("initialize should have failed");
+    } catch (err) {
+      expect((err as anchor.AnchorError).error.errorCode.code).to.equal("NoAuthoritySet");
+    }
+
+    await program.methods
+      .initialize(allowSeed, fee, user, true, null)
+      .accountsStrict(initAccounts)
+      .rpc();
+
+    const depositAccounts = {
+      user,
+      mintX,
+      mintY,
+      config: allowConfig,
+      allowedTrader: null,
+      mintLp: allowMintLp,
+      vaultX: allowVaultX,
+      vaultY: allowVaultY,
+      userX: userAtaX,
+      userY: userAtaY,
+      userLp: allowUserLp,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    };
+    const lpTokens = new anchor.BN(1_000_000);
+    const maxXAmt = new anchor.BN(1_000_000);
+    const maxYAmt = new anchor.BN(1_000_000);
+
+    try {
+      await program.methods.deposit(lpTokens, maxXAmt, maxYAmt).accountsStrict(depositAccounts).rpc();
+      assert.fail("deposit should have failed");
+    } catch (err) {
+      expect((err as anchor.AnchorError).error.errorCode.code).to.equal("TraderNotAllowed");
+    }
+
+    await program.methods
+      .allowTrader(user)
+      .accountsStrict({
+        authority: user,
+        config: allowConfig,
+        allowedTrader,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    await program.methods
+      .deposit(lpTokens, maxXAmt, maxYAmt)
+      .accountsStrict({ ...depositAccounts, allowedTrader })
+      .rpc();
+
+    const userLpTokens = await getAccount(provider.connection, allowUserLp);
+    expect(userLpTokens.amount.toString()).to.equal(lpTokens.toString());
+
+    const pairIndexAccount = await program.account.pairIndex.fetch(pairIndex);
+    expect(pairIndexAccount.poolCount.toNumber()).to.equal(2);
+    const entry = await program.account.poolEntry.fetch(poolEntry(1));
+    expect(entry.config.toBase58()).to.equal(allowConfig.toBase58());
+  });
+  //farm---------
+  it("stakes LP tokens in the farm, claims rewards and unstakes", async () => {
+    const rewardMint = await createMint(
+      provider.connection,
+      provider.wallet.payer,
+      user,
+      null,
+      decimals
+    );
+    const userReward = (
+      await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        provider.wallet.payer,
+        rewardMint,
+        user
+      )
+    ).address;
+    await mintTo(provider.connection, provider.wallet.payer, rewardMint, userReward, user, 1_000_000_000);
+
+    const [farm] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("farm"), configPda.toBuffer()],
+      program.programId
+    );
+    const [position] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("position"), farm.toBuffer(), user.toBuffer()],
+      program.programId
+    );
+    const rewardVault = getAssociatedTokenAddressSync(rewardMint, farm, true);
+    const lpVault = getAssociatedTokenAddressSync(mintLp, farm, true);
+
+    const endTime = new anchor.BN(Math.floor(Date.now() / 1000) + 3600);
+    await program.methods
+      .initFarm(new anchor.BN(1_000), endTime)
+      .accountsStrict({
+        authority: user,
+        config: configPda,
+        mintLp,
+        rewardMint,
+        farm,
+        rewardVault,
+        lpVault,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    await program.methods
+      .topUpFarm(new anchor.BN(100_000_000), endTime)
+      .accountsStrict({
+        authority: user,
+        config: configPda,
+        rewardMint,
+        farm,
+        rewardVault,
+        authorityReward: userReward,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    const stakeAmount = new anchor.BN((await getAccount(provider.connection, userLp)).amount.toString());
+    await program.methods
+      .stake(stakeAmount)
+      .accountsStrict({
+        user,
+        config: configPda,
+        allowedTrader: null,
+        mintLp,
+        farm,
+        position,
+        userLp,
+        lpVault,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    await new Promise(resolve => setTimeout(resolve, 3000));
+
+    const beforeClaim = await getAccount(provider.connection, userReward);
+    await program.methods
+      .claimRewards()
+      .accountsStrict({
+        user,
+        config: configPda,
+        rewardMint,
+        farm,
+        position,
+        rewardVault,
+        userReward,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+    const afterClaim = await getAccount(provider.connection, userReward);
+    assert(afterClaim.amount > beforeClaim.amount, "user should earn rewards while staked");
+
+    await program.methods
+      .unstake(stakeAmount)
+      .accountsStrict({
+        user,
+        config: configPda,
+        mintLp,
+        farm,
+        position,
+        userLp,
+        lpVault,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    const userLpTokens = await getAccount(provider.connection, userLp);
+    expect(userLpTokens.amount.toString()).to.equal(stakeAmount.toString());
+    const positionAccount = await program.account.stakePosition.fetch(position);
+    expect(positionAccount.amount.toNumber()).to.equal(0);
+  });
+  //dynamic fee---------
+  it("dynamic fee rises after a large swap, stays capped and decays back", async () => {
+    const dynSeed = new anchor.BN(9012);
+    const minFee = 10;
+    const maxFee = 500;
+    const decayPeriod = 10;
+
+    const [dynConfig] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("config"), dynSeed.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const [dynMintLp] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("lp"), dynConfig.toBuffer()],
+      program.programId
+    );
+    const dynVaultX = getAssociatedTokenAddressSync(mintX, dynConfig, true);
+    const dynVaultY = getAssociatedTokenAddressSync(mintY, dynConfig, true);
+
+    await program.methods
+      .initialize(dynSeed, fee, user, false, {
+        minFee,
+        maxFee,
+        decayPeriod: new anchor.BN(decayPeriod),
+      })
+      .accountsStrict({
+        initializer: user,
+        mintX,
+        mintY,
+        mintLp: dynMintLp,
+        vaultX: dynVaultX,
+        vaultY: dynVaultY,
+        config: dynConfig,
+        pairIndex,
+        poolEntry: poolEntry(2),
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    await program.methods
+      .deposit(new anchor.BN(100_000_000), new anchor.BN(100_000_000), new anchor.BN(200_000_000))
+      .accountsStrict({
+        user,
+        mintX,
+        mintY,
+        config: dynConfig,
+        allowedTrader: null,
+        mintLp: dynMintLp,
+        vaultX: dynVaultX,
+        vaultY: dynVaultY,
+        userX: userAtaX,
+        userY: userAtaY,
+        userLp: getAssociatedTokenAddressSync(dynMintLp, user),
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    // Buys exactly `out` Y with X and returns the X spent, along with the X the
+    // same swap costs at `fee` for the reserves it was made against.
+    const ceilDiv = (a: bigint, b: bigint) => (a + b - BigInt(1)) / b;
+    const buyY = async (out: number) => {
+      const reserveX = (await getAccount(provider.connection, dynVaultX)).amount;
+      const reserveY = (await getAccount(provider.connection, dynVaultY)).amount;
+      const before = (await getAccount(provider.connection, userAtaX)).amount;
+
+      await program.methods
+        .swapExactOut(true, new anchor.BN(out), new anchor.BN(1_000_000_000))
+        .accountsStrict({
+          user,
+          mintX,
+          mintY,
+          config: dynConfig,
+          allowedTrader: null,
+          mintLp: dynMintLp,
+          vaultX: dynVaultX,
+          vaultY: dynVaultY,
+          userX: userAtaX,
+          userY: userAtaY,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      const spent = before - (await getAccount(provider.connection, userAtaX)).amount;
+      const costAt = (fee: number) =>
+        ceilDiv(
+          ceilDiv(reserveX * BigInt(out), reserveY - BigInt(out)) * BigInt(10_000),
+          BigInt(10_000 - fee)
+        );
+      return { spent, costAt };
+    };
+
+    // A fresh pool charges the floor; halving the Y reserve moves the price a lot
+    const large = await buyY(100_000_000);
+    expect(large.spent.toString()).to.equal(large.costAt(minFee).toString());
+
+    const configAfterLarge = await program.account.config.fetch(dynConfig);
+    expect(configAfterLarge.dynamicFee.volatility.toNumber()).to.be.greaterThan(maxFee - minFee);
+
+    // Right after it the fee is up, but never past the ceiling
+    const next = await buyY(1_000_000);
+    expect(next.spent.toString()).to.equal(next.costAt(maxFee).toString());
+
+    // Once the decay period has passed without swaps it is back to the floor
+    await new Promise(resolve => setTimeout(resolve, (decayPeriod + 2) * 1000));
+    const calm = await buyY(1_000_000);
+    expect(calm.spent.toString()).to.equal(calm.costAt(minFee).toString());
+  });
+});
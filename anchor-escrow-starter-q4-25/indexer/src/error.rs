@@ -0,0 +1,13 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum IndexerError {
+    #[error("invalid base64 in program data: {0}")]
+    Base64(#[from] base64::DecodeError),
+    #[error("malformed escrow event: {0}")]
+    Event(#[from] std::io::Error),
+    #[error("invalid transaction json: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("transaction json has no log messages")]
+    MissingLogs,
+}
@@ -0,0 +1,39 @@
+use anchor_escrow_q4_25::{EscrowAmended, EscrowMade, EscrowRefunded, EscrowTaken};
+use anchor_lang::{AnchorDeserialize, Discriminator};
+
+use crate::IndexerError;
+
+#[derive(Clone, Debug)]
+pub enum EscrowEvent {
+    Made(EscrowMade),
+    Taken(EscrowTaken),
+    Refunded(EscrowRefunded),
+    Amended(EscrowAmended),
+}
+
+impl EscrowEvent {
+    // Decodes the payload of a `Program data:` log line. Events the order
+    // book does not track decode to `None` rather than an error.
+    pub fn decode(data: &[u8]) -> Result<Option<Self>, IndexerError> {
+        fn body<T: AnchorDeserialize + Discriminator>(
+            data: &[u8],
+        ) -> Option<Result<T, IndexerError>> {
+            data.strip_prefix(T::DISCRIMINATOR)
+                .map(|mut body| T::deserialize(&mut body).map_err(IndexerError::from))
+        }
+
+        if let Some(event) = body::<EscrowMade>(data) {
+            return event.map(|e| Some(Self::Made(e)));
+        }
+        if let Some(event) = body::<EscrowTaken>(data) {
+            return event.map(|e| Some(Self::Taken(e)));
+        }
+        if let Some(event) = body::<EscrowRefunded>(data) {
+            return event.map(|e| Some(Self::Refunded(e)));
+        }
+        if let Some(event) = body::<EscrowAmended>(data) {
+            return event.map(|e| Some(Self::Amended(e)));
+        }
+        Ok(None)
+    }
+}
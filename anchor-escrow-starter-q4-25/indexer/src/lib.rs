@@ -0,0 +1,15 @@
+//! Off-chain order book for the escrow program.
+//!
+//! Events emitted by `make`, `take`, `amend` and `refund` are decoded from
+//! program logs (or from `getTransaction` JSON) and folded into an
+//! [`OrderBook`] of open offers, keyed by the `(mint_a, mint_b)` pair.
+
+pub mod error;
+pub mod event;
+pub mod logs;
+pub mod order_book;
+
+pub use error::IndexerError;
+pub use event::EscrowEvent;
+pub use logs::{events_from_input, events_from_logs, events_from_transactions};
+pub use order_book::{Offer, OrderBook, Pair};
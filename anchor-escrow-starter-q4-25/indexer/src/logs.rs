@@ -0,0 +1,100 @@
+use anchor_lang::prelude::Pubkey;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::Value;
+
+use crate::{EscrowEvent, IndexerError};
+
+const PROGRAM_DATA: &str = "Program data: ";
+
+// Decodes the escrow events in one transaction's log messages.
+//
+// The runtime logs `Program <id> invoke [n]` / `Program <id> success` around
+// every invocation, so we track the call stack and only decode `Program data:`
+// lines written while the escrow program is the innermost frame. Any other
+// program can log bytes that look like our events; those are ignored.
+pub fn events_from_logs<'a>(
+    logs: impl IntoIterator<Item = &'a str>,
+    program_id: &Pubkey,
+) -> Result<Vec<EscrowEvent>, IndexerError> {
+    let program_id = program_id.to_string();
+    let mut stack: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+
+    for line in logs {
+        let line = line.trim();
+
+        if let Some(data) = line.strip_prefix(PROGRAM_DATA) {
+            if stack.last() == Some(&program_id.as_str()) {
+                if let Some(event) = EscrowEvent::decode(&STANDARD.decode(data.trim())?)? {
+                    events.push(event);
+                }
+            }
+            continue;
+        }
+
+        let Some(rest) = line.strip_prefix("Program ") else {
+            continue;
+        };
+        let mut words = rest.split_whitespace();
+        // `Program log:` / `Program return:` lines carry arbitrary text.
+        if words.clone().next().is_none_or(|word| word.ends_with(':')) {
+            continue;
+        }
+        match (words.next(), words.next()) {
+            (Some(id), Some("invoke")) => stack.push(id),
+            (Some(_), Some(status)) if status == "success" || status.starts_with("failed") => {
+                stack.pop();
+            }
+            _ => {}
+        }
+    }
+
+    Ok(events)
+}
+
+// Decodes events from `getTransaction` output. Accepts a single transaction,
+// an RPC response wrapping one in `result`, or an array of either. Failed
+// transactions are skipped since their events never took effect.
+pub fn events_from_transactions(
+    json: &Value,
+    program_id: &Pubkey,
+) -> Result<Vec<EscrowEvent>, IndexerError> {
+    if let Some(transactions) = json.as_array() {
+        let mut events = Vec::new();
+        for transaction in transactions {
+            events.extend(events_from_transactions(transaction, program_id)?);
+        }
+        return Ok(events);
+    }
+
+    let transaction = json.get("result").unwrap_or(json);
+    if transaction.is_null() {
+        return Ok(Vec::new());
+    }
+
+    let meta = transaction.get("meta").ok_or(IndexerError::MissingLogs)?;
+    if meta.get("err").is_some_and(|err| !err.is_null()) {
+        return Ok(Vec::new());
+    }
+
+    let logs = meta
+        .get("logMessages")
+        .and_then(Value::as_array)
+        .ok_or(IndexerError::MissingLogs)?;
+
+    events_from_logs(logs.iter().filter_map(Value::as_str), program_id)
+}
+
+// Reads either transaction JSON or raw log lines, whichever `input` holds.
+pub fn events_from_input(
+    input: &str,
+    program_id: &Pubkey,
+) -> Result<Vec<EscrowEvent>, IndexerError> {
+    let trimmed = input.trim_start();
+    if trimmed.starts_with('{') || trimmed.starts_with('[') {
+        let json: Value = serde_json::from_str(trimmed)?;
+        events_from_transactions(&json, program_id)
+    } else {
+        events_from_logs(input.lines(), program_id)
+    }
+}
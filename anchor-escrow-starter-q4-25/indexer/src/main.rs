@@ -0,0 +1,90 @@
+// Builds the escrow order book from a file (or stdin) of program logs or
+// `getTransaction` JSON and prints the open asks and bids per mint pair.
+//
+//     escrow-indexer [FILE] [--now UNIX_TIMESTAMP]
+
+use std::{
+    io::{self, Read},
+    process::ExitCode,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use escrow_indexer::{events_from_input, OrderBook};
+
+fn main() -> ExitCode {
+    let mut path = None;
+    let mut now = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--now" => now = args.next().and_then(|value| value.parse::<i64>().ok()),
+            _ => path = Some(arg),
+        }
+    }
+
+    let input = match path {
+        Some(path) => std::fs::read_to_string(&path),
+        None => {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input).map(|_| input)
+        }
+    };
+    let input = match input {
+        Ok(input) => input,
+        Err(err) => {
+            eprintln!("failed to read input: {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let events = match events_from_input(&input, &anchor_escrow_q4_25::ID) {
+        Ok(events) => events,
+        Err(err) => {
+            eprintln!("{err}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut book = OrderBook::new();
+    book.apply_all(&events);
+
+    let now = now.unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs() as i64)
+    });
+
+    let mut pairs: Vec<_> = book.pairs().copied().collect();
+    pairs.sort();
+    for (base, quote) in pairs {
+        println!("{base} / {quote}");
+        let best = |price: Option<f64>| price.map_or("-".to_string(), |price| price.to_string());
+        println!("  best ask: {}", best(book.best_price(&base, &quote, now)));
+        println!(
+            "  best bid: {}",
+            best(book.best_bid_price(&base, &quote, now))
+        );
+        for offer in book
+            .offers(&base, &quote)
+            .into_iter()
+            .chain(book.bids(&base, &quote))
+        {
+            println!(
+                "  {:?} {} price={} remaining={} receive={} expires_at={}{}",
+                offer.side,
+                offer.escrow,
+                offer.price(),
+                offer.remaining,
+                offer.remaining_receive(),
+                offer.expires_at,
+                if offer.taker.is_some() {
+                    " private"
+                } else {
+                    ""
+                },
+            );
+        }
+    }
+
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,237 @@
+use std::{cmp::Ordering, collections::HashMap};
+
+use anchor_escrow_q4_25::{Pricing, Side};
+use anchor_lang::prelude::Pubkey;
+
+use crate::EscrowEvent;
+
+// (base, quote): the asset being traded and the mint it is priced in. Asks
+// sell mint_a for mint_b and bids buy mint_b with mint_a, so an ask and a
+// bid for the same asset land in the same book.
+pub type Pair = (Pubkey, Pubkey);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Offer {
+    pub escrow: Pubkey,
+    pub maker: Pubkey,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub deposit: u64,
+    pub receive: u64,
+    pub remaining: u64,
+    pub expires_at: i64,
+    pub taker: Option<Pubkey>,
+    pub pricing: Pricing,
+    pub side: Side,
+    pub version: u32,
+}
+
+impl Offer {
+    pub fn pair(&self) -> Pair {
+        match self.side {
+            Side::Ask => (self.mint_a, self.mint_b),
+            Side::Bid => (self.mint_b, self.mint_a),
+        }
+    }
+
+    // Quote base units per base unit: mint_b per mint_a for asks, mint_a per
+    // mint_b for bids. For oracle and auction offers this is only the floor.
+    pub fn price(&self) -> f64 {
+        let (quote, base) = self.quote_and_base();
+        quote as f64 / base as f64
+    }
+
+    // mint_b still owed for the unfilled part, rounded the way `take` rounds.
+    // For bids this is the amount of mint_b the maker still wants.
+    pub fn remaining_receive(&self) -> u64 {
+        match self.side {
+            Side::Ask => {
+                let filled = (self.deposit - self.remaining) as u128;
+                let paid = (filled * self.receive as u128).div_ceil(self.deposit as u128);
+                self.receive - paid as u64
+            }
+            Side::Bid => self.remaining,
+        }
+    }
+
+    pub fn is_open(&self, now: i64) -> bool {
+        self.expires_at > now
+    }
+
+    fn quote_and_base(&self) -> (u64, u64) {
+        match self.side {
+            Side::Ask => (self.receive, self.deposit),
+            Side::Bid => (self.deposit, self.receive),
+        }
+    }
+
+    // Better offers sort first: the cheapest ask, the highest bid. Prices are
+    // compared by cross-multiplying so two offers at the same ratio compare
+    // equal regardless of size.
+    fn cmp_price(&self, other: &Self) -> Ordering {
+        let (quote, base) = self.quote_and_base();
+        let (other_quote, other_base) = other.quote_and_base();
+        let lhs = quote as u128 * other_base as u128;
+        let rhs = other_quote as u128 * base as u128;
+        let by_price = match self.side {
+            Side::Ask => lhs.cmp(&rhs),
+            Side::Bid => rhs.cmp(&lhs),
+        };
+        by_price
+            .then(other.remaining.cmp(&self.remaining))
+            .then(self.escrow.cmp(&other.escrow))
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct OrderBook {
+    books: HashMap<Pair, HashMap<Pubkey, Offer>>,
+    pairs: HashMap<Pubkey, Pair>,
+}
+
+impl OrderBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Events for escrows made before the indexer started are ignored, since
+    // their terms are unknown.
+    pub fn apply(&mut self, event: &EscrowEvent) {
+        match event {
+            EscrowEvent::Made(made) => {
+                let offer = Offer {
+                    escrow: made.escrow,
+                    maker: made.maker,
+                    mint_a: made.mint_a,
+                    mint_b: made.mint_b,
+                    deposit: made.deposit,
+                    receive: made.receive,
+                    remaining: match made.side {
+                        Side::Ask => made.deposit,
+                        Side::Bid => made.receive,
+                    },
+                    expires_at: made.expires_at,
+                    taker: made.taker,
+                    pricing: made.pricing,
+                    side: made.side,
+                    version: 0,
+                };
+                let pair = offer.pair();
+                self.pairs.insert(made.escrow, pair);
+                self.books
+                    .entry(pair)
+                    .or_default()
+                    .insert(made.escrow, offer);
+            }
+            EscrowEvent::Taken(taken) => {
+                if taken.remaining == 0 {
+                    self.remove(&taken.escrow);
+                } else if let Some(offer) = self.get_mut(&taken.escrow) {
+                    offer.remaining = taken.remaining;
+                }
+            }
+            EscrowEvent::Refunded(refunded) => self.remove(&refunded.escrow),
+            EscrowEvent::Amended(amended) => {
+                if let Some(offer) = self.get_mut(&amended.escrow) {
+                    offer.deposit = amended.new_deposit;
+                    offer.remaining = amended.new_remaining;
+                    offer.receive = amended.new_receive;
+                    offer.version = amended.version;
+                }
+            }
+        }
+    }
+
+    pub fn apply_all<'a>(&mut self, events: impl IntoIterator<Item = &'a EscrowEvent>) {
+        for event in events {
+            self.apply(event);
+        }
+    }
+
+    pub fn get(&self, escrow: &Pubkey) -> Option<&Offer> {
+        let pair = self.pairs.get(escrow)?;
+        self.books.get(pair)?.get(escrow)
+    }
+
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+
+    pub fn pairs(&self) -> impl Iterator<Item = &Pair> {
+        self.books.keys()
+    }
+
+    // Every ask selling `base` for `quote`, cheapest first.
+    pub fn offers(&self, base: &Pubkey, quote: &Pubkey) -> Vec<&Offer> {
+        self.side(base, quote, Side::Ask)
+    }
+
+    // Every bid buying `base` with `quote`, highest first.
+    pub fn bids(&self, base: &Pubkey, quote: &Pubkey) -> Vec<&Offer> {
+        self.side(base, quote, Side::Bid)
+    }
+
+    // Cheapest ask anyone can take at `now`: expired offers and offers
+    // locked to a designated taker are skipped, as are oracle and auction
+    // offers since their price depends on when they are taken.
+    pub fn best_offer(&self, base: &Pubkey, quote: &Pubkey, now: i64) -> Option<&Offer> {
+        self.best(base, quote, Side::Ask, now)
+    }
+
+    // Highest bid anyone can fill at `now`.
+    pub fn best_bid(&self, base: &Pubkey, quote: &Pubkey, now: i64) -> Option<&Offer> {
+        self.best(base, quote, Side::Bid, now)
+    }
+
+    pub fn best_price(&self, base: &Pubkey, quote: &Pubkey, now: i64) -> Option<f64> {
+        self.best_offer(base, quote, now).map(Offer::price)
+    }
+
+    pub fn best_bid_price(&self, base: &Pubkey, quote: &Pubkey, now: i64) -> Option<f64> {
+        self.best_bid(base, quote, now).map(Offer::price)
+    }
+
+    fn side(&self, base: &Pubkey, quote: &Pubkey, side: Side) -> Vec<&Offer> {
+        let mut offers: Vec<&Offer> = self
+            .books
+            .get(&(*base, *quote))
+            .map(|book| book.values().filter(|offer| offer.side == side).collect())
+            .unwrap_or_default();
+        offers.sort_by(|a, b| a.cmp_price(b));
+        offers
+    }
+
+    fn best(&self, base: &Pubkey, quote: &Pubkey, side: Side, now: i64) -> Option<&Offer> {
+        self.books
+            .get(&(*base, *quote))?
+            .values()
+            .filter(|offer| {
+                offer.side == side
+                    && offer.is_open(now)
+                    && offer.taker.is_none()
+                    && offer.pricing.is_fixed()
+            })
+            .min_by(|a, b| a.cmp_price(b))
+    }
+
+    fn get_mut(&mut self, escrow: &Pubkey) -> Option<&mut Offer> {
+        let pair = self.pairs.get(escrow)?;
+        self.books.get_mut(pair)?.get_mut(escrow)
+    }
+
+    fn remove(&mut self, escrow: &Pubkey) {
+        let Some(pair) = self.pairs.remove(escrow) else {
+            return;
+        };
+        if let Some(book) = self.books.get_mut(&pair) {
+            book.remove(escrow);
+            if book.is_empty() {
+                self.books.remove(&pair);
+            }
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use anchor_escrow_q4_25::{EscrowMade, EscrowTaken, Pricing, Side, ID};
+use anchor_lang::prelude::Pubkey;
+use escrow_indexer::{events_from_input, events_from_logs, EscrowEvent, OrderBook};
+
+const NOW: i64 = 1_760_000_000;
+
+fn key(n: u8) -> Pubkey {
+    Pubkey::new_from_array([n; 32])
+}
+
+fn book_from_fixture(name: &str) -> OrderBook {
+    let path = format!("{}/tests/fixtures/{name}", env!("CARGO_MANIFEST_DIR"));
+    let input = std::fs::read_to_string(path).unwrap();
+    let mut book = OrderBook::new();
+    book.apply_all(&events_from_input(&input, &ID).unwrap());
+    book
+}
+
+#[test]
+fn builds_book_from_transaction_json() {
+    let book = book_from_fixture("transactions.json");
+
+    // The failed make and the event logged by another program are dropped.
+    assert_eq!(book.len(), 6);
+    assert!(book.get(&key(24)).is_none());
+    assert!(book.get(&key(15)).is_none());
+
+    let offers: Vec<_> = book
+        .offers(&key(10), &key(11))
+        .into_iter()
+        .map(|offer| offer.escrow)
+        .collect();
+    assert_eq!(offers, vec![key(23), key(22), key(21)]);
+
+    let partially_filled = book.get(&key(21)).unwrap();
+    assert_eq!(partially_filled.remaining, 75);
+    assert_eq!(partially_filled.remaining_receive(), 150);
+}
+
+#[test]
+fn best_offer_skips_private_and_expired_offers() {
+    let book = book_from_fixture("transactions.json");
+
+    // Escrow 23 is cheaper but locked to a designated taker.
+    let best = book.best_offer(&key(10), &key(11), NOW).unwrap();
+    assert_eq!(best.escrow, key(22));
+    assert_eq!(book.best_price(&key(10), &key(11), NOW), Some(1.8));
+
+    // The only offer on the reverse pair expired long ago.
+    assert_eq!(book.offers(&key(11), &key(10)).len(), 1);
+    assert!(book.best_offer(&key(11), &key(10), NOW).is_none());
+}
+
+#[test]
+fn bids_share_the_book_with_asks() {
+    let book = book_from_fixture("transactions.json");
+
+    // Bids pay mint 11 for mint 10, so they sit in the (10, 11) book.
+    let bids: Vec<_> = book
+        .bids(&key(10), &key(11))
+        .into_iter()
+        .map(|bid| bid.escrow)
+        .collect();
+    assert_eq!(bids, vec![key(28), key(27)]);
+    assert_eq!(book.best_bid_price(&key(10), &key(11), NOW), Some(1.7));
+
+    let partially_filled = book.get(&key(28)).unwrap();
+    assert_eq!(partially_filled.side, Side::Bid);
+    assert_eq!(partially_filled.remaining_receive(), 60);
+
+    // Asks are unaffected by the bids on the same pair.
+    assert_eq!(book.offers(&key(10), &key(11)).len(), 3);
+}
+
+#[test]
+fn applies_amendments_and_refunds_from_raw_logs() {
+    let book = book_from_fixture("lifecycle.log");
+
+    assert_eq!(book.len(), 1);
+    assert!(book.get(&key(32)).is_none());
+
+    let amended = book.get(&key(31)).unwrap();
+    assert_eq!(amended.version, 1);
+    assert_eq!(amended.remaining, 40);
+    assert_eq!(amended.remaining_receive(), 100);
+    assert_eq!(book.best_price(&key(12), &key(10), NOW), Some(2.5));
+
+    let path = format!(
+        "{}/tests/fixtures/lifecycle.log",
+        env!("CARGO_MANIFEST_DIR")
+    );
+    let input = std::fs::read_to_string(path).unwrap();
+    let events = events_from_input(&input, &ID).unwrap();
+    let Some(EscrowEvent::Amended(amended)) = events.get(2) else {
+        panic!("expected the amendment as the third event");
+    };
+    assert_eq!(amended.side, Side::Ask);
+    assert_eq!(amended.old_deposit, 100);
+    assert_eq!(amended.new_deposit, 40);
+}
+
+#[test]
+fn fully_filled_offers_leave_the_book() {
+    let mut book = OrderBook::new();
+    book.apply(&EscrowEvent::Made(EscrowMade {
+        escrow: key(1),
+        maker: key(2),
+        seed: 1,
+        mint_a: key(3),
+        mint_b: key(4),
+        deposit: 10,
+        receive: 20,
+        expires_at: i64::MAX,
+        taker: None,
+        pricing: Pricing::Fixed,
+        side: Side::Ask,
+    }));
+    book.apply(&EscrowEvent::Taken(EscrowTaken {
+        escrow: key(1),
+        taker: key(5),
+        amount: 10,
+        paid: 20,
+        remaining: 0,
+    }));
+
+    assert!(book.is_empty());
+    assert_eq!(book.pairs().count(), 0);
+}
+
+#[test]
+fn ignores_log_lines_that_only_look_like_invocations() {
+    let logs = [
+        format!("Program {ID} invoke [1]"),
+        "Program log: success".to_string(),
+        "Program data: AAAAAAAAAAA=".to_string(),
+        format!("Program {ID} success"),
+    ];
+
+    let events = events_from_logs(logs.iter().map(String::as_str), &ID).unwrap();
+    assert!(events.is_empty());
+}
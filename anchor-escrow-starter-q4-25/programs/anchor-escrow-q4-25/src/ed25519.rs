@@ -0,0 +1,79 @@
+use anchor_lang::prelude::*;
+
+// Checks a signature verified by the ed25519 precompile in the same
+// transaction, by reading the precompile's instruction through the
+// instructions sysvar. The precompile fails the whole transaction when a
+// signature is invalid, so all that is left to check here is that it
+// verified the expected signer over the expected message.
+
+use crate::errors::EscrowError;
+use solana_instructions_sysvar::{load_current_index_checked, load_instruction_at_checked};
+use solana_sdk_ids::ed25519_program;
+
+// Header: number of signatures and a padding byte, then one offsets
+// record of seven u16s per signature.
+const HEADER_LEN: usize = 2;
+const OFFSETS_LEN: usize = 14;
+const PUBKEY_LEN: usize = 32;
+const SIGNATURE_LEN: usize = 64;
+// Instruction index meaning "this instruction's own data".
+const CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+// Requires the instruction right before the current one to be an ed25519
+// precompile call verifying exactly one signature by `signer` over `message`.
+pub fn verify_signed_message(
+    instructions_sysvar: &AccountInfo,
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<()> {
+    let current = load_current_index_checked(instructions_sysvar)?;
+    require_gt!(current, 0, EscrowError::InvalidIntent);
+
+    let ix = load_instruction_at_checked(current as usize - 1, instructions_sysvar)?;
+    require_keys_eq!(
+        ix.program_id,
+        ed25519_program::ID,
+        EscrowError::InvalidIntent
+    );
+    require!(ix.accounts.is_empty(), EscrowError::InvalidIntent);
+
+    let data = &ix.data;
+    require!(
+        data.len() >= HEADER_LEN + OFFSETS_LEN && data[0] == 1,
+        EscrowError::InvalidIntent
+    );
+
+    let u16_at = |index: usize| {
+        let offset = HEADER_LEN + index * 2;
+        u16::from_le_bytes([data[offset], data[offset + 1]])
+    };
+    let signature_offset = u16_at(0) as usize;
+    let public_key_offset = u16_at(2) as usize;
+    let message_offset = u16_at(4) as usize;
+    let message_len = u16_at(5) as usize;
+
+    // Everything must come from the precompile instruction itself, or the
+    // offsets could point at data this check never sees.
+    require!(
+        [u16_at(1), u16_at(3), u16_at(6)]
+            .iter()
+            .all(|&index| index == CURRENT_INSTRUCTION),
+        EscrowError::InvalidIntent
+    );
+    require!(
+        data.len() >= signature_offset + SIGNATURE_LEN
+            && data.len() >= public_key_offset + PUBKEY_LEN
+            && data.len() >= message_offset + message_len,
+        EscrowError::InvalidIntent
+    );
+
+    require!(
+        &data[public_key_offset..public_key_offset + PUBKEY_LEN] == signer.as_ref(),
+        EscrowError::InvalidIntent
+    );
+    require!(
+        &data[message_offset..message_offset + message_len] == message,
+        EscrowError::InvalidIntent
+    );
+    Ok(())
+}
@@ -10,4 +10,66 @@ pub enum EscrowError {
     InvalidMintA,
     #[msg("Invalid mint b")]
     InvalidMintB,
+    #[msg("Invalid expiry")]
+    InvalidExpiry,
+    #[msg("Offer expired")]
+    OfferExpired,
+    #[msg("Offer not expired")]
+    OfferNotExpired,
+    #[msg("Invalid taker")]
+    InvalidTaker,
+    #[msg("Mint a and mint b must differ")]
+    IdenticalMints,
+    #[msg("Escrow terms differ from what the taker expected")]
+    TermsChanged,
+    #[msg("Invalid basket legs")]
+    InvalidBasket,
+    #[msg("Remaining accounts do not match the escrow")]
+    InvalidRemainingAccounts,
+    #[msg("Invalid Core asset")]
+    InvalidAsset,
+    #[msg("Collection does not match the asset")]
+    InvalidCollection,
+    #[msg("Invalid fee settings")]
+    InvalidFee,
+    #[msg("Invalid treasury account")]
+    InvalidTreasury,
+    #[msg("Invalid authority")]
+    InvalidAuthority,
+    #[msg("Invalid pricing parameters")]
+    InvalidPricing,
+    #[msg("Invalid oracle account")]
+    InvalidOracle,
+    #[msg("Oracle price is stale")]
+    StalePrice,
+    #[msg("Oracle confidence interval too wide")]
+    PriceUncertain,
+    #[msg("Current price is above the taker's limit")]
+    PriceAboveLimit,
+    #[msg("Offer side not supported by this instruction")]
+    InvalidSide,
+    #[msg("Filled less than the taker's minimum")]
+    InsufficientFill,
+    #[msg("Invalid beneficiary")]
+    InvalidBeneficiary,
+    #[msg("Invalid arbiter")]
+    InvalidArbiter,
+    #[msg("Not allowed in the escrow's current status")]
+    InvalidStatus,
+    #[msg("Timeout has not been reached")]
+    TimeoutNotReached,
+    #[msg("Invalid milestone schedule")]
+    InvalidMilestones,
+    #[msg("Invalid vesting schedule")]
+    InvalidSchedule,
+    #[msg("Nothing to claim yet")]
+    NothingToClaim,
+    #[msg("Grant is not revocable")]
+    NotRevocable,
+    #[msg("Rent payer does not match the escrow")]
+    InvalidRentPayer,
+    #[msg("Intent signature does not match the maker or the terms")]
+    InvalidIntent,
+    #[msg("Intent deadline has passed")]
+    IntentExpired,
 }
\ No newline at end of file
@@ -0,0 +1,57 @@
+use anchor_lang::prelude::*;
+
+use crate::{Pricing, Side};
+
+// Events carry the full terms of the offer so an indexer can rebuild the
+// set of open escrows from logs alone, without fetching accounts.
+
+#[event]
+#[derive(Clone, Debug)]
+pub struct EscrowMade {
+    pub escrow: Pubkey,
+    pub maker: Pubkey,
+    pub seed: u64,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub deposit: u64,
+    pub receive: u64,
+    pub expires_at: i64,
+    pub taker: Option<Pubkey>,
+    pub pricing: Pricing,
+    pub side: Side,
+}
+
+#[event]
+#[derive(Clone, Debug)]
+pub struct EscrowTaken {
+    pub escrow: Pubkey,
+    pub taker: Pubkey,
+    pub amount: u64,
+    pub paid: u64,
+    pub remaining: u64,
+}
+
+#[event]
+#[derive(Clone, Debug)]
+pub struct EscrowRefunded {
+    pub escrow: Pubkey,
+    pub maker: Pubkey,
+    pub amount: u64,
+}
+
+// `old_remaining` and `new_remaining` are counted in the unit fills are
+// counted in: mint_a for an ask, mint_b for a bid.
+#[event]
+#[derive(Clone, Debug)]
+pub struct EscrowAmended {
+    pub escrow: Pubkey,
+    pub maker: Pubkey,
+    pub version: u32,
+    pub side: Side,
+    pub old_deposit: u64,
+    pub new_deposit: u64,
+    pub old_remaining: u64,
+    pub new_remaining: u64,
+    pub old_receive: u64,
+    pub new_receive: u64,
+}
@@ -0,0 +1,138 @@
+use anchor_lang::prelude::*;
+
+// Lets the maker change an open offer in place instead of refunding and
+// making it again. The vault is topped up or drained to the new `deposit`,
+// `receive` is replaced, and the version is bumped so takers can pin the
+// exact terms they saw.
+//
+// The amended amounts become the new pricing basis: later fills are priced
+// pro-rata against the amended terms, not the original ones.
+
+use crate::{errors::EscrowError, events::EscrowAmended, Escrow, Side};
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+#[derive(Accounts)]
+pub struct Amend<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint_a: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program,
+    )]
+    pub maker_ata_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        has_one = mint_a @ EscrowError::InvalidMintA,
+        has_one = maker @ EscrowError::InvalidMaker,
+        seeds = [b"escrow", escrow.maker.as_ref(), &escrow.seed.to_le_bytes()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> Amend<'info> {
+    pub fn rebalance_vault(&mut self, deposit: u64) -> Result<()> {
+        let remaining = self.vault.amount;
+
+        if deposit > remaining {
+            let transfer_accounts = TransferChecked {
+                from: self.maker_ata_a.to_account_info(),
+                mint: self.mint_a.to_account_info(),
+                to: self.vault.to_account_info(),
+                authority: self.maker.to_account_info(),
+            };
+
+            let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), transfer_accounts);
+
+            transfer_checked(cpi_ctx, deposit - remaining, self.mint_a.decimals)?;
+        } else if deposit < remaining {
+            let signer_seeds: &[&[&[u8]]] = &[&[
+                b"escrow",
+                self.maker.to_account_info().key.as_ref(),
+                &self.escrow.seed.to_le_bytes(),
+                &[self.escrow.bump],
+            ]];
+
+            let transfer_accounts = TransferChecked {
+                from: self.vault.to_account_info(),
+                mint: self.mint_a.to_account_info(),
+                to: self.maker_ata_a.to_account_info(),
+                authority: self.escrow.to_account_info(),
+            };
+
+            let cpi_ctx = CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                transfer_accounts,
+                signer_seeds,
+            );
+
+            transfer_checked(cpi_ctx, remaining - deposit, self.mint_a.decimals)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn update_terms(&mut self, deposit: u64, receive: u64) -> Result<()> {
+        let old_deposit = self.escrow.deposit;
+        let old_remaining = self.escrow.remaining;
+        let old_receive = self.escrow.receive;
+
+        self.escrow.deposit = deposit;
+        self.escrow.remaining = match self.escrow.side {
+            Side::Ask => deposit,
+            Side::Bid => receive,
+        };
+        self.escrow.receive = receive;
+        self.escrow.version = self
+            .escrow
+            .version
+            .checked_add(1)
+            .ok_or(EscrowError::InvalidAmount)?;
+
+        emit!(EscrowAmended {
+            escrow: self.escrow.key(),
+            maker: self.maker.key(),
+            version: self.escrow.version,
+            side: self.escrow.side,
+            old_deposit,
+            new_deposit: deposit,
+            old_remaining,
+            new_remaining: self.escrow.remaining,
+            old_receive,
+            new_receive: receive,
+        });
+
+        Ok(())
+    }
+
+    pub fn handler(ctx: Context<Amend>, deposit: u64, receive: u64) -> Result<()> {
+        require_gt!(receive, 0, EscrowError::InvalidAmount);
+        require_gt!(deposit, 0, EscrowError::InvalidAmount);
+        require_gt!(
+            ctx.accounts.escrow.expires_at,
+            Clock::get()?.unix_timestamp,
+            EscrowError::OfferExpired
+        );
+
+        ctx.accounts.rebalance_vault(deposit)?;
+        ctx.accounts.update_terms(deposit, receive)
+    }
+}
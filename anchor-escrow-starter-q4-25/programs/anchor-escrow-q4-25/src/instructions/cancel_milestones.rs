@@ -0,0 +1,91 @@
+use anchor_lang::prelude::*;
+
+// Drops the rest of the schedule once the maker disputed the current
+// milestone: every milestone after it goes back to the maker. The disputed
+// milestone itself stays in the vault until the arbiter rules on it or its
+// ruling period runs out, so the maker cannot use a dispute to claw back a
+// payment that was about to fall due.
+
+use crate::{errors::EscrowError, MilestoneEscrow};
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+#[derive(Accounts)]
+pub struct CancelMilestones<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program
+    )]
+    pub maker_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        has_one = maker @ EscrowError::InvalidMaker,
+        has_one = mint @ EscrowError::InvalidMintA,
+        seeds = [b"milestones", milestones.maker.as_ref(), &milestones.seed.to_le_bytes()],
+        bump = milestones.bump
+    )]
+    pub milestones: Account<'info, MilestoneEscrow>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = milestones,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> CancelMilestones<'info> {
+    pub fn refund_later_milestones(&mut self) -> Result<()> {
+        let milestone = self
+            .milestones
+            .current()
+            .ok_or(EscrowError::InvalidStatus)?;
+        require!(milestone.disputed, EscrowError::InvalidStatus);
+
+        let keep = self.milestones.next as usize + 1;
+        let refund = self.milestones.milestones[keep..]
+            .iter()
+            .try_fold(0u64, |total, m| total.checked_add(m.amount))
+            .ok_or(EscrowError::InvalidAmount)?;
+        require_gt!(refund, 0, EscrowError::InvalidStatus);
+
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"milestones",
+            self.maker.to_account_info().key.as_ref(),
+            &self.milestones.seed.to_le_bytes(),
+            &[self.milestones.bump],
+        ]];
+
+        let transfer_accounts = TransferChecked {
+            from: self.vault.to_account_info(),
+            mint: self.mint.to_account_info(),
+            to: self.maker_ata.to_account_info(),
+            authority: self.milestones.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            transfer_accounts,
+            signer_seeds,
+        );
+
+        transfer_checked(cpi_ctx, refund, self.mint.decimals)?;
+
+        // The disputed milestone is now the last one and settles the vault.
+        self.milestones.milestones.truncate(keep);
+        Ok(())
+    }
+}
@@ -0,0 +1,140 @@
+use anchor_lang::prelude::*;
+
+// Either side ends the stream. The recipient is paid what it has earned and
+// not yet withdrawn, the sender gets the unearned balance back, and the vault
+// and the stream are closed to the sender. Cancelling after `stop` simply
+// settles a finished stream.
+
+// The accounts needed in this context are:
+
+//     authority: the sender or the recipient; pays for a missing ATA
+//     sender: the user that funded the stream and receives the rent back
+//     recipient: the user the stream pays
+//     mint: the streamed token
+//     sender_ata: the sender's token account for the unearned balance
+//     recipient_ata: the recipient's token account for the earned balance
+//     stream: the stream account
+//     vault: the stream's token account holding the balance
+
+use crate::{errors::EscrowError, PaymentStream};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
+        TransferChecked,
+    },
+};
+
+#[derive(Accounts)]
+pub struct CancelStream<'info> {
+    #[account(
+        mut,
+        constraint = authority.key() == sender.key() || authority.key() == recipient.key() @ EscrowError::InvalidAuthority
+    )]
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub sender: SystemAccount<'info>,
+    pub recipient: SystemAccount<'info>,
+
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = mint,
+        associated_token::authority = sender,
+        associated_token::token_program = token_program
+    )]
+    pub sender_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = mint,
+        associated_token::authority = recipient,
+        associated_token::token_program = token_program
+    )]
+    pub recipient_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        close = sender,
+        has_one = sender @ EscrowError::InvalidMaker,
+        has_one = recipient @ EscrowError::InvalidBeneficiary,
+        has_one = mint @ EscrowError::InvalidMintA,
+        seeds = [b"stream", stream.sender.as_ref(), &stream.seed.to_le_bytes()],
+        bump = stream.bump
+    )]
+    pub stream: Account<'info, PaymentStream>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = stream,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CancelStream<'info> {
+    pub fn settle_and_close_vault(&mut self) -> Result<()> {
+        let to_recipient = self.stream.withdrawable(Clock::get()?.unix_timestamp);
+        let to_sender = self.vault.amount - to_recipient;
+
+        self.pay(&self.recipient_ata, to_recipient)?;
+        self.pay(&self.sender_ata, to_sender)?;
+
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"stream",
+            self.sender.to_account_info().key.as_ref(),
+            &self.stream.seed.to_le_bytes(),
+            &[self.stream.bump],
+        ]];
+
+        let close_accounts = CloseAccount {
+            account: self.vault.to_account_info(),
+            destination: self.sender.to_account_info(),
+            authority: self.stream.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            close_accounts,
+            signer_seeds,
+        );
+
+        close_account(cpi_ctx)
+    }
+
+    fn pay(&self, to: &InterfaceAccount<'info, TokenAccount>, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"stream",
+            self.sender.to_account_info().key.as_ref(),
+            &self.stream.seed.to_le_bytes(),
+            &[self.stream.bump],
+        ]];
+
+        let transfer_accounts = TransferChecked {
+            from: self.vault.to_account_info(),
+            mint: self.mint.to_account_info(),
+            to: to.to_account_info(),
+            authority: self.stream.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            transfer_accounts,
+            signer_seeds,
+        );
+
+        transfer_checked(cpi_ctx, amount, self.mint.decimals)
+    }
+}
@@ -0,0 +1,138 @@
+use anchor_lang::prelude::*;
+
+// The beneficiary withdraws everything vested so far. The claim that empties
+// the schedule closes the vault and the schedule, returning the rent and any
+// tokens sent to the vault from outside the grant to the grantor. The
+// grantor's token account is only needed for that last claim, and only when
+// such tokens are left in the vault.
+
+use crate::{errors::EscrowError, VestingSchedule};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
+        TransferChecked,
+    },
+};
+
+#[derive(Accounts)]
+pub struct ClaimVested<'info> {
+    #[account(mut)]
+    pub beneficiary: Signer<'info>,
+    #[account(mut)]
+    pub grantor: SystemAccount<'info>,
+
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init_if_needed,
+        payer = beneficiary,
+        associated_token::mint = mint,
+        associated_token::authority = beneficiary,
+        associated_token::token_program = token_program
+    )]
+    pub beneficiary_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = grantor,
+        associated_token::token_program = token_program
+    )]
+    pub grantor_ata: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        has_one = grantor @ EscrowError::InvalidMaker,
+        has_one = beneficiary @ EscrowError::InvalidBeneficiary,
+        has_one = mint @ EscrowError::InvalidMintA,
+        seeds = [b"vesting", vesting.grantor.as_ref(), &vesting.seed.to_le_bytes()],
+        bump = vesting.bump
+    )]
+    pub vesting: Account<'info, VestingSchedule>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = vesting,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ClaimVested<'info> {
+    pub fn claim(&mut self) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let amount = self.vesting.claimable(now);
+        require_gt!(amount, 0, EscrowError::NothingToClaim);
+
+        self.pay(&self.beneficiary_ata, amount)?;
+
+        self.vesting.claimed += amount;
+
+        if !self.vesting.is_settled(now) {
+            return Ok(());
+        }
+
+        self.vault.reload()?;
+        if self.vault.amount > 0 {
+            let grantor_ata = self.grantor_ata.as_ref().ok_or(EscrowError::InvalidMaker)?;
+            self.pay(grantor_ata, self.vault.amount)?;
+        }
+
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"vesting",
+            self.grantor.to_account_info().key.as_ref(),
+            &self.vesting.seed.to_le_bytes(),
+            &[self.vesting.bump],
+        ]];
+
+        let close_accounts = CloseAccount {
+            account: self.vault.to_account_info(),
+            destination: self.grantor.to_account_info(),
+            authority: self.vesting.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            close_accounts,
+            signer_seeds,
+        );
+
+        close_account(cpi_ctx)?;
+
+        self.vesting.close(self.grantor.to_account_info())
+    }
+
+    fn pay(&self, to: &InterfaceAccount<'info, TokenAccount>, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"vesting",
+            self.grantor.to_account_info().key.as_ref(),
+            &self.vesting.seed.to_le_bytes(),
+            &[self.vesting.bump],
+        ]];
+
+        let transfer_accounts = TransferChecked {
+            from: self.vault.to_account_info(),
+            mint: self.mint.to_account_info(),
+            to: to.to_account_info(),
+            authority: self.vesting.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            transfer_accounts,
+            signer_seeds,
+        );
+
+        transfer_checked(cpi_ctx, amount, self.mint.decimals)
+    }
+}
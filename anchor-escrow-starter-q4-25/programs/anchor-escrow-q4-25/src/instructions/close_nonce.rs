@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+
+// Permissionless cleanup for spent intent nonces. Once the intent's deadline
+// has passed it can no longer be submitted, so the nonce marker is no longer
+// needed and its rent goes back to the relayer that paid it.
+
+use crate::{errors::EscrowError, UsedNonce};
+
+#[derive(Accounts)]
+pub struct CloseNonce<'info> {
+    pub cranker: Signer<'info>,
+    #[account(mut)]
+    pub payer: SystemAccount<'info>,
+    #[account(
+        mut,
+        close = payer,
+        has_one = payer @ EscrowError::InvalidRentPayer,
+        seeds = [b"nonce", used_nonce.maker.as_ref(), &used_nonce.nonce.to_le_bytes()],
+        bump = used_nonce.bump
+    )]
+    pub used_nonce: Account<'info, UsedNonce>,
+}
+
+impl<'info> CloseNonce<'info> {
+    pub fn require_expired(&self) -> Result<()> {
+        require_gte!(
+            Clock::get()?.unix_timestamp,
+            self.used_nonce.deadline,
+            EscrowError::TimeoutNotReached
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,125 @@
+use anchor_lang::prelude::*;
+
+// Basket version of `crank_expired`: once the basket is past its `expires_at`,
+// anyone can send every offered leg back to the maker and close the vaults and
+// the basket, with their rent going back to the maker.
+
+// Remaining accounts, three per offered leg, in the order of `offered`:
+//     mint: the offered mint
+//     vault: the basket's ATA for that mint
+//     maker_ata: the maker's ATA for that mint, created if needed
+
+use crate::{
+    errors::EscrowError,
+    utils::{create_ata_if_needed, load_mint_decimals, require_ata},
+    BasketEscrow,
+};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        close_account, transfer_checked, CloseAccount, TokenAccount, TokenInterface,
+        TransferChecked,
+    },
+};
+
+#[derive(Accounts)]
+pub struct CrankExpiredBasket<'info> {
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        close = maker,
+        has_one = maker @ EscrowError::InvalidMaker,
+        seeds = [b"basket", basket.maker.as_ref(), &basket.seed.to_le_bytes()],
+        bump = basket.bump
+    )]
+    pub basket: Account<'info, BasketEscrow>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CrankExpiredBasket<'info> {
+    pub fn return_and_close_vaults(
+        &mut self,
+        remaining_accounts: &'info [AccountInfo<'info>],
+    ) -> Result<()> {
+        require_gte!(
+            Clock::get()?.unix_timestamp,
+            self.basket.expires_at,
+            EscrowError::OfferNotExpired
+        );
+        require_eq!(
+            remaining_accounts.len(),
+            self.basket.offered.len() * 3,
+            EscrowError::InvalidRemainingAccounts
+        );
+
+        let token_program = self.token_program.key();
+
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"basket",
+            self.maker.to_account_info().key.as_ref(),
+            &self.basket.seed.to_le_bytes(),
+            &[self.basket.bump],
+        ]];
+
+        for (leg, accounts) in self.basket.offered.iter().zip(remaining_accounts.chunks(3)) {
+            let [mint, vault, maker_ata] = accounts else {
+                return err!(EscrowError::InvalidRemainingAccounts);
+            };
+
+            let decimals = load_mint_decimals(mint, &leg.mint, &token_program)?;
+            require_ata(vault, &self.basket.key(), &leg.mint, &token_program)?;
+            require_ata(maker_ata, &self.maker.key(), &leg.mint, &token_program)?;
+
+            // The maker may have closed their token account since making the basket
+            create_ata_if_needed(
+                self.cranker.to_account_info(),
+                maker_ata.clone(),
+                self.maker.to_account_info(),
+                mint.clone(),
+                self.system_program.to_account_info(),
+                self.token_program.to_account_info(),
+                self.associated_token_program.to_account_info(),
+            )?;
+
+            let amount = InterfaceAccount::<TokenAccount>::try_from(vault)?.amount;
+
+            let transfer_accounts = TransferChecked {
+                from: vault.clone(),
+                mint: mint.clone(),
+                to: maker_ata.clone(),
+                authority: self.basket.to_account_info(),
+            };
+
+            let cpi_ctx = CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                transfer_accounts,
+                signer_seeds,
+            );
+
+            transfer_checked(cpi_ctx, amount, decimals)?;
+
+            let close_accounts = CloseAccount {
+                account: vault.clone(),
+                destination: self.maker.to_account_info(),
+                authority: self.basket.to_account_info(),
+            };
+
+            let cpi_ctx = CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                close_accounts,
+                signer_seeds,
+            );
+
+            close_account(cpi_ctx)?;
+        }
+
+        Ok(())
+    }
+}
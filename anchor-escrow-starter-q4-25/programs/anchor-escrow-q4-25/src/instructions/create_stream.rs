@@ -0,0 +1,106 @@
+use anchor_lang::prelude::*;
+
+// Opens a payment stream: the sender deposits an initial balance into a vault
+// owned by the stream PDA, and the recipient earns `rate` tokens per second
+// between `start` and `stop`.
+
+use crate::{errors::EscrowError, PaymentStream};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct CreateStream<'info> {
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = sender,
+        associated_token::token_program = token_program
+    )]
+    pub sender_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = sender,
+        seeds = [b"stream", sender.key().as_ref(), seed.to_le_bytes().as_ref()],
+        space = PaymentStream::DISCRIMINATOR.len() + PaymentStream::INIT_SPACE,
+        bump
+    )]
+    pub stream: Account<'info, PaymentStream>,
+    #[account(
+        init,
+        payer = sender,
+        associated_token::mint = mint,
+        associated_token::authority = stream,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreateStream<'info> {
+    #[allow(clippy::too_many_arguments)]
+    pub fn init_stream(
+        &mut self,
+        seed: u64,
+        recipient: Pubkey,
+        rate: u64,
+        start: i64,
+        stop: i64,
+        deposit: u64,
+        bumps: &CreateStreamBumps,
+    ) -> Result<()> {
+        require_gt!(rate, 0, EscrowError::InvalidAmount);
+        require_gt!(deposit, 0, EscrowError::InvalidAmount);
+        require_gt!(stop, start, EscrowError::InvalidSchedule);
+        require_gt!(
+            stop,
+            Clock::get()?.unix_timestamp,
+            EscrowError::InvalidSchedule
+        );
+        require_keys_neq!(
+            recipient,
+            self.sender.key(),
+            EscrowError::InvalidBeneficiary
+        );
+
+        self.stream.set_inner(PaymentStream {
+            seed,
+            sender: self.sender.key(),
+            recipient,
+            mint: self.mint.key(),
+            rate,
+            start,
+            stop,
+            deposited: deposit,
+            withdrawn: 0,
+            bump: bumps.stream,
+        });
+        Ok(())
+    }
+
+    pub fn deposit(&mut self, amount: u64) -> Result<()> {
+        let transfer_accounts = TransferChecked {
+            from: self.sender_ata.to_account_info(),
+            mint: self.mint.to_account_info(),
+            to: self.vault.to_account_info(),
+            authority: self.sender.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), transfer_accounts);
+
+        transfer_checked(cpi_ctx, amount, self.mint.decimals)
+    }
+}
@@ -0,0 +1,107 @@
+use anchor_lang::prelude::*;
+
+// Locks a token grant in a vault owned by the vesting PDA, to be claimed by
+// the beneficiary as it vests.
+
+use crate::{errors::EscrowError, VestingSchedule};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct CreateVesting<'info> {
+    #[account(mut)]
+    pub grantor: Signer<'info>,
+
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = grantor,
+        associated_token::token_program = token_program
+    )]
+    pub grantor_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = grantor,
+        seeds = [b"vesting", grantor.key().as_ref(), seed.to_le_bytes().as_ref()],
+        space = VestingSchedule::DISCRIMINATOR.len() + VestingSchedule::INIT_SPACE,
+        bump
+    )]
+    pub vesting: Account<'info, VestingSchedule>,
+    #[account(
+        init,
+        payer = grantor,
+        associated_token::mint = mint,
+        associated_token::authority = vesting,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreateVesting<'info> {
+    #[allow(clippy::too_many_arguments)]
+    pub fn init_vesting(
+        &mut self,
+        seed: u64,
+        beneficiary: Pubkey,
+        total: u64,
+        start: i64,
+        cliff: u32,
+        duration: u32,
+        period: u32,
+        revocable: bool,
+        bumps: &CreateVestingBumps,
+    ) -> Result<()> {
+        require_gt!(total, 0, EscrowError::InvalidAmount);
+        require_keys_neq!(
+            beneficiary,
+            self.grantor.key(),
+            EscrowError::InvalidBeneficiary
+        );
+        require!(
+            period > 0 && period <= duration && cliff <= duration,
+            EscrowError::InvalidSchedule
+        );
+
+        self.vesting.set_inner(VestingSchedule {
+            seed,
+            grantor: self.grantor.key(),
+            beneficiary,
+            mint: self.mint.key(),
+            total,
+            claimed: 0,
+            start,
+            cliff,
+            duration,
+            period,
+            revocable,
+            revoked: false,
+            bump: bumps.vesting,
+        });
+        Ok(())
+    }
+
+    pub fn deposit(&mut self, amount: u64) -> Result<()> {
+        let transfer_accounts = TransferChecked {
+            from: self.grantor_ata.to_account_info(),
+            mint: self.mint.to_account_info(),
+            to: self.vault.to_account_info(),
+            authority: self.grantor.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), transfer_accounts);
+
+        transfer_checked(cpi_ctx, amount, self.mint.decimals)
+    }
+}
@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+
+// The maker contests the work and hands the decision to the arbiter, who
+// then has `ruling_period` seconds before the fallback split applies.
+// A delivery can only be disputed while its review period is running.
+
+use crate::{errors::EscrowError, ArbitratedEscrow, ArbitrationStatus};
+
+#[derive(Accounts)]
+pub struct Dispute<'info> {
+    pub maker: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = maker @ EscrowError::InvalidMaker,
+        seeds = [b"arbitrated", arbitrated.maker.as_ref(), &arbitrated.seed.to_le_bytes()],
+        bump = arbitrated.bump
+    )]
+    pub arbitrated: Account<'info, ArbitratedEscrow>,
+}
+
+impl<'info> Dispute<'info> {
+    pub fn dispute(&mut self) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+
+        match self.arbitrated.status {
+            ArbitrationStatus::Funded => {}
+            ArbitrationStatus::Delivered => require_gt!(
+                self.arbitrated
+                    .updated_at
+                    .saturating_add(self.arbitrated.review_period as i64),
+                now,
+                EscrowError::InvalidStatus
+            ),
+            ArbitrationStatus::Disputed => return err!(EscrowError::InvalidStatus),
+        }
+
+        self.arbitrated.status = ArbitrationStatus::Disputed;
+        self.arbitrated.updated_at = now;
+        Ok(())
+    }
+}
@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+
+// The maker flags the current milestone before its deadline, so it no longer
+// pays out on its own. The arbiter then has `ruling_period` seconds to split
+// it; the maker can still approve it in full, or take back the milestones
+// after it with `cancel_milestones`.
+
+use crate::{errors::EscrowError, MilestoneEscrow};
+
+#[derive(Accounts)]
+pub struct DisputeMilestone<'info> {
+    pub maker: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = maker @ EscrowError::InvalidMaker,
+        seeds = [b"milestones", milestones.maker.as_ref(), &milestones.seed.to_le_bytes()],
+        bump = milestones.bump
+    )]
+    pub milestones: Account<'info, MilestoneEscrow>,
+}
+
+impl<'info> DisputeMilestone<'info> {
+    pub fn dispute(&mut self) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let next = self.milestones.next as usize;
+        let milestone = self
+            .milestones
+            .milestones
+            .get_mut(next)
+            .ok_or(EscrowError::InvalidStatus)?;
+
+        require!(!milestone.disputed, EscrowError::InvalidStatus);
+        require_gt!(milestone.deadline, now, EscrowError::OfferExpired);
+
+        milestone.disputed = true;
+        self.milestones.disputed_at = now;
+        Ok(())
+    }
+}
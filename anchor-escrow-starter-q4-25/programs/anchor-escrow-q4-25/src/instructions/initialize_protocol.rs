@@ -0,0 +1,54 @@
+use anchor_lang::prelude::*;
+
+// Creates the singleton protocol config that `take` reads its fee from.
+// Only the program's upgrade authority can create it; the signer then
+// becomes the authority allowed to change the settings later.
+
+use crate::{errors::EscrowError, program::AnchorEscrowQ425, ProtocolConfig};
+
+#[derive(Accounts)]
+pub struct InitializeProtocol<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"protocol"],
+        space = ProtocolConfig::DISCRIMINATOR.len() + ProtocolConfig::INIT_SPACE,
+        bump
+    )]
+    pub protocol: Account<'info, ProtocolConfig>,
+
+    #[account(
+        constraint = program.programdata_address()? == Some(program_data.key()) @ EscrowError::InvalidAuthority
+    )]
+    pub program: Program<'info, AnchorEscrowQ425>,
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(authority.key()) @ EscrowError::InvalidAuthority
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitializeProtocol<'info> {
+    pub fn init_protocol(
+        &mut self,
+        treasury: Pubkey,
+        fee_bps: u16,
+        referral_bps: u16,
+        bumps: &InitializeProtocolBumps,
+    ) -> Result<()> {
+        ProtocolConfig::validate(fee_bps, referral_bps)?;
+
+        self.protocol.set_inner(ProtocolConfig {
+            authority: self.authority.key(),
+            treasury,
+            fee_bps,
+            referral_bps,
+            bump: bumps.protocol,
+        });
+        Ok(())
+    }
+}
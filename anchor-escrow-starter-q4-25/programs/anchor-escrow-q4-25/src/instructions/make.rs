@@ -8,16 +8,18 @@ use anchor_lang::prelude::*;
 // The accounts needed in this context are:
 
 // maker: the user that decides the terms and deposits the mint_a into the Escrow
+// payer: the account funding the rent of the escrow and the vault, usually the maker
 // escrow: the account holding the exchange terms (maker, mints, amounts)
 // mint_a: the token that the maker is depositing
 // mint_b: the token that the maker wants in exchange
 // maker_ata_a: the token account associated with the maker and mint_a used to deposit tokens in the vault
 // vault: the token account associated with the escrow and mint_a where deposited tokens are parked
+// protocol: the protocol config PDA; its fee settings are copied onto the escrow
 // associated_token_program: the associated token program used to create the associated token accounts
 // token_program: the token program used to CPI the transfer
 // system_program: the system program used to create the Escrow
 
-use crate::Escrow;
+use crate::{events::EscrowMade, Escrow, Pricing, ProtocolConfig, Side};
 use anchor_spl::{
     associated_token::AssociatedToken,
     token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
@@ -29,6 +31,8 @@ use crate::errors::EscrowError;
 pub struct Make<'info> {
     #[account(mut)]
     pub maker: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
 
     // token account 
     #[account(
@@ -37,7 +41,8 @@ pub struct Make<'info> {
     pub mint_a: InterfaceAccount<'info, Mint>,
     // token account
     #[account(
-        mint::token_program = token_program
+        mint::token_program = token_program,
+        constraint = mint_b.key() != mint_a.key() @ EscrowError::IdenticalMints
     )]
     pub mint_b: InterfaceAccount<'info, Mint>,
     // token account
@@ -51,7 +56,7 @@ pub struct Make<'info> {
     
     #[account(
         init,
-        payer = maker,
+        payer = payer,
         seeds = [b"escrow", maker.key().as_ref(), seed.to_le_bytes().as_ref()],
         space = Escrow::DISCRIMINATOR.len() + Escrow::INIT_SPACE,
         bump
@@ -61,28 +66,76 @@ pub struct Make<'info> {
     // token account
     #[account(
         init,
-        payer = maker,
+        payer = payer,
         associated_token::mint = mint_a,
         associated_token::authority = escrow,
         associated_token::token_program = token_program
     )]
     pub vault: InterfaceAccount<'info, TokenAccount>,
 
+    /// CHECK: Protocol config PDA; it may not exist yet, so it is read with `ProtocolConfig::fee_terms`
+    #[account(
+        seeds = [b"protocol"],
+        bump
+    )]
+    pub protocol: UncheckedAccount<'info>,
+
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
 
 impl<'info> Make<'info> {
-    pub fn init_escrow(&mut self, seed: u64, receive: u64, bumps: &MakeBumps) -> Result<()> {
+    #[allow(clippy::too_many_arguments)]
+    pub fn init_escrow(
+        &mut self,
+        seed: u64,
+        deposit: u64,
+        receive: u64,
+        expires_at: i64,
+        taker: Option<Pubkey>,
+        pricing: Pricing,
+        side: Side,
+        bumps: &MakeBumps,
+    ) -> Result<()> {
+        let (fee_bps, referral_bps) = ProtocolConfig::fee_terms(&self.protocol)?;
+
         self.escrow.set_inner(Escrow {
             seed,
             maker: self.maker.key(),
             mint_a: self.mint_a.key(),
             mint_b: self.mint_b.key(),
             receive,
+            deposit,
+            remaining: match side {
+                Side::Ask => deposit,
+                Side::Bid => receive,
+            },
+            expires_at,
+            taker,
+            pricing,
+            side,
+            version: 0,
+            fee_bps,
+            referral_bps,
+            rent_payer: self.payer.key(),
+            vault_rent_payer: self.payer.key(),
             bump: bumps.escrow,
         });
+
+        emit!(EscrowMade {
+            escrow: self.escrow.key(),
+            maker: self.maker.key(),
+            seed,
+            mint_a: self.mint_a.key(),
+            mint_b: self.mint_b.key(),
+            deposit,
+            receive,
+            expires_at,
+            taker,
+            pricing,
+            side,
+        });
         Ok(())
     }
 
@@ -101,16 +154,54 @@ impl<'info> Make<'info> {
         Ok(())
     }
 
-    pub fn handler(ctx: Context<Make>, seed: u64, receive: u64, amount: u64) -> Result<()> {
-        // Validate the amount
+    pub fn handler(
+        ctx: Context<Make>,
+        seed: u64,
+        deposit: u64,
+        receive: u64,
+        expires_at: i64,
+        taker: Option<Pubkey>,
+        pricing: Pricing,
+    ) -> Result<()> {
+        Self::open(ctx, seed, deposit, receive, expires_at, taker, pricing, Side::Ask)
+    }
+
+    // A bid locks `deposit` of the payment mint (mint_a) for `receive` of the
+    // wanted mint (mint_b). Bids are always fixed-price.
+    pub fn bid_handler(
+        ctx: Context<Make>,
+        seed: u64,
+        deposit: u64,
+        receive: u64,
+        expires_at: i64,
+        taker: Option<Pubkey>,
+    ) -> Result<()> {
+        Self::open(ctx, seed, deposit, receive, expires_at, taker, Pricing::Fixed, Side::Bid)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn open(
+        ctx: Context<Make>,
+        seed: u64,
+        deposit: u64,
+        receive: u64,
+        expires_at: i64,
+        taker: Option<Pubkey>,
+        pricing: Pricing,
+        side: Side,
+    ) -> Result<()> {
+        // Validate the terms
         require_gt!(receive, 0, EscrowError::InvalidAmount);
-        require_gt!(amount, 0, EscrowError::InvalidAmount);
+        require_gt!(deposit, 0, EscrowError::InvalidAmount);
+        require_gt!(expires_at, Clock::get()?.unix_timestamp, EscrowError::InvalidExpiry);
+        pricing.validate()?;
     
         // Save the Escrow Data
-        ctx.accounts.init_escrow(seed, receive, &ctx.bumps)?;
+        ctx.accounts
+            .init_escrow(seed, deposit, receive, expires_at, taker, pricing, side, &ctx.bumps)?;
     
         // Deposit Tokens
-        ctx.accounts.deposit(amount)?;
+        ctx.accounts.deposit(deposit)?;
     
         Ok(())
     }
@@ -0,0 +1,111 @@
+use anchor_lang::prelude::*;
+
+// Opens an arbitrated escrow for a service deal: the maker locks the payment
+// in a vault owned by the escrow PDA and names who gets paid for the work
+// and who rules if the maker disputes it.
+
+use crate::{errors::EscrowError, ArbitratedEscrow, ArbitrationStatus, BPS_DENOMINATOR};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct MakeArbitrated<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program
+    )]
+    pub maker_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = maker,
+        seeds = [b"arbitrated", maker.key().as_ref(), seed.to_le_bytes().as_ref()],
+        space = ArbitratedEscrow::DISCRIMINATOR.len() + ArbitratedEscrow::INIT_SPACE,
+        bump
+    )]
+    pub arbitrated: Account<'info, ArbitratedEscrow>,
+    #[account(
+        init,
+        payer = maker,
+        associated_token::mint = mint,
+        associated_token::authority = arbitrated,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MakeArbitrated<'info> {
+    #[allow(clippy::too_many_arguments)]
+    pub fn init_arbitrated(
+        &mut self,
+        seed: u64,
+        amount: u64,
+        beneficiary: Pubkey,
+        arbiter: Pubkey,
+        review_period: u32,
+        ruling_period: u32,
+        fallback_bps: u16,
+        bumps: &MakeArbitratedBumps,
+    ) -> Result<()> {
+        let maker = self.maker.key();
+        require_gt!(amount, 0, EscrowError::InvalidAmount);
+        require!(
+            beneficiary != maker && beneficiary != arbiter,
+            EscrowError::InvalidBeneficiary
+        );
+        require_keys_neq!(arbiter, maker, EscrowError::InvalidArbiter);
+        require!(
+            review_period > 0 && ruling_period > 0,
+            EscrowError::InvalidExpiry
+        );
+        require!(
+            fallback_bps as u64 <= BPS_DENOMINATOR,
+            EscrowError::InvalidAmount
+        );
+
+        self.arbitrated.set_inner(ArbitratedEscrow {
+            seed,
+            maker,
+            beneficiary,
+            arbiter,
+            mint: self.mint.key(),
+            amount,
+            review_period,
+            ruling_period,
+            fallback_bps,
+            status: ArbitrationStatus::Funded,
+            updated_at: Clock::get()?.unix_timestamp,
+            bump: bumps.arbitrated,
+        });
+        Ok(())
+    }
+
+    pub fn deposit(&mut self, amount: u64) -> Result<()> {
+        let transfer_accounts = TransferChecked {
+            from: self.maker_ata.to_account_info(),
+            mint: self.mint.to_account_info(),
+            to: self.vault.to_account_info(),
+            authority: self.maker.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), transfer_accounts);
+
+        transfer_checked(cpi_ctx, amount, self.mint.decimals)
+    }
+}
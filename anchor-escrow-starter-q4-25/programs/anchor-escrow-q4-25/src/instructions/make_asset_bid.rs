@@ -0,0 +1,93 @@
+use anchor_lang::prelude::*;
+
+// Maker offers tokens for a Metaplex Core asset.
+// mint_a is parked in a vault owned by the bid PDA, exactly like `Make`.
+// The target is either one specific asset or any asset of a collection.
+
+use crate::{errors::EscrowError, AssetBid, CoreAssetTarget};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct MakeAssetBid<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint_a: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program
+    )]
+    pub maker_ata_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = maker,
+        seeds = [b"asset_bid", maker.key().as_ref(), seed.to_le_bytes().as_ref()],
+        space = AssetBid::DISCRIMINATOR.len() + AssetBid::INIT_SPACE,
+        bump
+    )]
+    pub bid: Account<'info, AssetBid>,
+    #[account(
+        init,
+        payer = maker,
+        associated_token::mint = mint_a,
+        associated_token::authority = bid,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MakeAssetBid<'info> {
+    pub fn init_bid(
+        &mut self,
+        seed: u64,
+        deposit: u64,
+        target: CoreAssetTarget,
+        expires_at: i64,
+        bumps: &MakeAssetBidBumps,
+    ) -> Result<()> {
+        require_gt!(deposit, 0, EscrowError::InvalidAmount);
+        require_gt!(
+            expires_at,
+            Clock::get()?.unix_timestamp,
+            EscrowError::InvalidExpiry
+        );
+
+        self.bid.set_inner(AssetBid {
+            seed,
+            maker: self.maker.key(),
+            mint_a: self.mint_a.key(),
+            deposit,
+            target,
+            expires_at,
+            bump: bumps.bid,
+        });
+        Ok(())
+    }
+
+    pub fn deposit(&mut self, deposit: u64) -> Result<()> {
+        let transfer_accounts = TransferChecked {
+            from: self.maker_ata_a.to_account_info(),
+            mint: self.mint_a.to_account_info(),
+            to: self.vault.to_account_info(),
+            authority: self.maker.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), transfer_accounts);
+
+        transfer_checked(cpi_ctx, deposit, self.mint_a.decimals)
+    }
+}
@@ -0,0 +1,90 @@
+use anchor_lang::prelude::*;
+
+// Maker sells a Metaplex Core asset for tokens.
+// The asset is moved into the custody of the offer PDA with Core's TransferV1
+// and stays there until a taker pays `receive` of mint_b or the maker refunds.
+
+use crate::{
+    errors::EscrowError,
+    mpl_core::{transfer_v1, CoreAsset, MPL_CORE_ID},
+    AssetOffer,
+};
+use anchor_spl::token_interface::{Mint, TokenInterface};
+
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct MakeAssetOffer<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    /// CHECK: Core asset, owner and layout are checked in `CoreAsset::load`
+    #[account(mut)]
+    pub asset: UncheckedAccount<'info>,
+    /// CHECK: Must match the asset's collection, checked in `CoreAsset::require_collection`
+    pub collection: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint_b: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = maker,
+        seeds = [b"asset_offer", maker.key().as_ref(), seed.to_le_bytes().as_ref()],
+        space = AssetOffer::DISCRIMINATOR.len() + AssetOffer::INIT_SPACE,
+        bump
+    )]
+    pub offer: Account<'info, AssetOffer>,
+
+    /// CHECK: Metaplex Core program
+    #[account(address = MPL_CORE_ID)]
+    pub mpl_core_program: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MakeAssetOffer<'info> {
+    pub fn init_offer(
+        &mut self,
+        seed: u64,
+        receive: u64,
+        expires_at: i64,
+        bumps: &MakeAssetOfferBumps,
+    ) -> Result<()> {
+        require_gt!(receive, 0, EscrowError::InvalidAmount);
+        require_gt!(
+            expires_at,
+            Clock::get()?.unix_timestamp,
+            EscrowError::InvalidExpiry
+        );
+
+        self.offer.set_inner(AssetOffer {
+            seed,
+            maker: self.maker.key(),
+            asset: self.asset.key(),
+            mint_b: self.mint_b.key(),
+            receive,
+            expires_at,
+            bump: bumps.offer,
+        });
+        Ok(())
+    }
+
+    pub fn deposit_asset(&mut self) -> Result<()> {
+        let asset = CoreAsset::load(&self.asset)?;
+        require_keys_eq!(asset.owner, self.maker.key(), EscrowError::InvalidAsset);
+        asset.require_collection(self.collection.as_deref())?;
+
+        transfer_v1(
+            self.asset.to_account_info(),
+            self.collection.as_ref().map(|c| c.to_account_info()),
+            self.maker.to_account_info(),
+            self.maker.to_account_info(),
+            self.offer.to_account_info(),
+            self.system_program.to_account_info(),
+            self.mpl_core_program.to_account_info(),
+            &[],
+        )
+    }
+}
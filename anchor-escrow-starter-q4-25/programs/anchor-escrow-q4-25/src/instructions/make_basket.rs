@@ -0,0 +1,135 @@
+use anchor_lang::prelude::*;
+
+// Basket version of `make`: the maker deposits up to MAX_BASKET_LEGS mints and
+// asks for up to MAX_BASKET_LEGS mints in return.
+
+// Remaining accounts, three per offered leg, in the order of `offered`:
+//     mint: the offered mint
+//     maker_ata: the maker's ATA for that mint, tokens are moved out of it
+//     vault: the basket's ATA for that mint, created here
+
+use crate::{
+    errors::EscrowError,
+    utils::{create_ata_if_needed, load_mint_decimals, require_ata},
+    BasketEscrow, BasketLeg, MAX_BASKET_LEGS,
+};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{transfer_checked, TokenInterface, TransferChecked},
+};
+
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct MakeBasket<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(
+        init,
+        payer = maker,
+        seeds = [b"basket", maker.key().as_ref(), seed.to_le_bytes().as_ref()],
+        space = BasketEscrow::DISCRIMINATOR.len() + BasketEscrow::INIT_SPACE,
+        bump
+    )]
+    pub basket: Account<'info, BasketEscrow>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MakeBasket<'info> {
+    pub fn init_basket(
+        &mut self,
+        seed: u64,
+        offered: Vec<BasketLeg>,
+        requested: Vec<BasketLeg>,
+        expires_at: i64,
+        bumps: &MakeBasketBumps,
+    ) -> Result<()> {
+        require_gt!(
+            expires_at,
+            Clock::get()?.unix_timestamp,
+            EscrowError::InvalidExpiry
+        );
+        Self::validate_legs(&offered)?;
+        Self::validate_legs(&requested)?;
+        require!(
+            offered
+                .iter()
+                .all(|o| requested.iter().all(|r| r.mint != o.mint)),
+            EscrowError::IdenticalMints
+        );
+
+        self.basket.set_inner(BasketEscrow {
+            seed,
+            maker: self.maker.key(),
+            offered,
+            requested,
+            expires_at,
+            bump: bumps.basket,
+        });
+        Ok(())
+    }
+
+    pub fn deposit(&mut self, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+        require_eq!(
+            remaining_accounts.len(),
+            self.basket.offered.len() * 3,
+            EscrowError::InvalidRemainingAccounts
+        );
+
+        let token_program = self.token_program.key();
+
+        for (leg, accounts) in self.basket.offered.iter().zip(remaining_accounts.chunks(3)) {
+            let [mint, maker_ata, vault] = accounts else {
+                return err!(EscrowError::InvalidRemainingAccounts);
+            };
+
+            let decimals = load_mint_decimals(mint, &leg.mint, &token_program)?;
+            require_ata(maker_ata, &self.maker.key(), &leg.mint, &token_program)?;
+            require_ata(vault, &self.basket.key(), &leg.mint, &token_program)?;
+
+            create_ata_if_needed(
+                self.maker.to_account_info(),
+                vault.clone(),
+                self.basket.to_account_info(),
+                mint.clone(),
+                self.system_program.to_account_info(),
+                self.token_program.to_account_info(),
+                self.associated_token_program.to_account_info(),
+            )?;
+
+            let transfer_accounts = TransferChecked {
+                from: maker_ata.clone(),
+                mint: mint.clone(),
+                to: vault.clone(),
+                authority: self.maker.to_account_info(),
+            };
+
+            let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), transfer_accounts);
+
+            transfer_checked(cpi_ctx, leg.amount, decimals)?;
+        }
+
+        Ok(())
+    }
+
+    fn validate_legs(legs: &[BasketLeg]) -> Result<()> {
+        require!(
+            !legs.is_empty() && legs.len() <= MAX_BASKET_LEGS,
+            EscrowError::InvalidBasket
+        );
+
+        for (i, leg) in legs.iter().enumerate() {
+            require_gt!(leg.amount, 0, EscrowError::InvalidAmount);
+            // One vault per mint, so a mint can only appear once per side
+            require!(
+                legs[..i].iter().all(|other| other.mint != leg.mint),
+                EscrowError::InvalidBasket
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,134 @@
+use anchor_lang::prelude::*;
+
+// Opens a milestone escrow: the maker deposits the sum of an ordered list of
+// milestone payments into a vault owned by the escrow PDA. Each milestone
+// can name a different recipient. The arbiter rules on any milestone the
+// maker disputes.
+
+use crate::{errors::EscrowError, Milestone, MilestoneEscrow, BPS_DENOMINATOR, MAX_MILESTONES};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct MilestoneTerms {
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub deadline: i64,
+}
+
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct MakeMilestones<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program
+    )]
+    pub maker_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = maker,
+        seeds = [b"milestones", maker.key().as_ref(), seed.to_le_bytes().as_ref()],
+        space = MilestoneEscrow::DISCRIMINATOR.len() + MilestoneEscrow::INIT_SPACE,
+        bump
+    )]
+    pub milestones: Account<'info, MilestoneEscrow>,
+    #[account(
+        init,
+        payer = maker,
+        associated_token::mint = mint,
+        associated_token::authority = milestones,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MakeMilestones<'info> {
+    // Returns the total to deposit.
+    pub fn init_milestones(
+        &mut self,
+        seed: u64,
+        terms: Vec<MilestoneTerms>,
+        arbiter: Pubkey,
+        ruling_period: u32,
+        fallback_bps: u16,
+        bumps: &MakeMilestonesBumps,
+    ) -> Result<u64> {
+        require!(
+            !terms.is_empty() && terms.len() <= MAX_MILESTONES,
+            EscrowError::InvalidMilestones
+        );
+        require_keys_neq!(arbiter, self.maker.key(), EscrowError::InvalidArbiter);
+        require_gt!(ruling_period, 0, EscrowError::InvalidExpiry);
+        require!(
+            fallback_bps as u64 <= BPS_DENOMINATOR,
+            EscrowError::InvalidAmount
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let mut total: u64 = 0;
+        let mut last_deadline = now;
+        for milestone in &terms {
+            require_gt!(milestone.amount, 0, EscrowError::InvalidAmount);
+            require_gt!(
+                milestone.deadline,
+                last_deadline,
+                EscrowError::InvalidMilestones
+            );
+            last_deadline = milestone.deadline;
+            total = total
+                .checked_add(milestone.amount)
+                .ok_or(EscrowError::InvalidAmount)?;
+        }
+
+        self.milestones.set_inner(MilestoneEscrow {
+            seed,
+            maker: self.maker.key(),
+            mint: self.mint.key(),
+            arbiter,
+            ruling_period,
+            fallback_bps,
+            milestones: terms
+                .into_iter()
+                .map(|t| Milestone {
+                    recipient: t.recipient,
+                    amount: t.amount,
+                    deadline: t.deadline,
+                    disputed: false,
+                })
+                .collect(),
+            next: 0,
+            disputed_at: 0,
+            bump: bumps.milestones,
+        });
+        Ok(total)
+    }
+
+    pub fn deposit(&mut self, amount: u64) -> Result<()> {
+        let transfer_accounts = TransferChecked {
+            from: self.maker_ata.to_account_info(),
+            mint: self.mint.to_account_info(),
+            to: self.vault.to_account_info(),
+            authority: self.maker.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), transfer_accounts);
+
+        transfer_checked(cpi_ctx, amount, self.mint.decimals)
+    }
+}
@@ -0,0 +1,186 @@
+use anchor_lang::prelude::*;
+
+// Gasless version of `make`: the maker only signs a `MakeIntent` off-chain.
+// A relayer (or the taker) submits it after an ed25519 precompile
+// instruction carrying that signature, pays the rent, and the maker's
+// Token A is pulled into the vault through the program's delegate PDA,
+// which the maker approved on their token account beforehand.
+
+// The accounts needed in this context are:
+
+//     payer: the relayer submitting the intent; pays the rent and fees
+//     maker: the user that signed the intent
+//     mint_a: the token that the maker is depositing
+//     mint_b: the token that the maker wants in exchange
+//     maker_ata_a: the maker's token account for mint_a, with `delegate` approved on it
+//     delegate: the program-wide PDA makers approve to move their deposit
+//     escrow: the account holding the exchange terms
+//     vault: the escrow's token account for mint_a
+//     used_nonce: marks the intent's nonce as spent
+//     protocol: the protocol config PDA; its fee settings are copied onto the escrow
+//     instructions: the instructions sysvar, read to find the ed25519 instruction
+
+use crate::{
+    ed25519::verify_signed_message, errors::EscrowError, events::EscrowMade, Escrow, MakeIntent,
+    ProtocolConfig, Side, UsedNonce,
+};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+#[derive(Accounts)]
+#[instruction(intent: MakeIntent)]
+pub struct MakeWithIntent<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        address = intent.maker @ EscrowError::InvalidMaker
+    )]
+    pub maker: SystemAccount<'info>,
+
+    #[account(
+        address = intent.mint_a @ EscrowError::InvalidMintA,
+        mint::token_program = token_program
+    )]
+    pub mint_a: InterfaceAccount<'info, Mint>,
+    #[account(
+        address = intent.mint_b @ EscrowError::InvalidMintB,
+        mint::token_program = token_program,
+        constraint = mint_b.key() != mint_a.key() @ EscrowError::IdenticalMints
+    )]
+    pub mint_b: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program
+    )]
+    pub maker_ata_a: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: Data-less PDA that only signs as the delegate of makers' token accounts
+    #[account(
+        seeds = [b"delegate"],
+        bump
+    )]
+    pub delegate: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"escrow", maker.key().as_ref(), intent.seed.to_le_bytes().as_ref()],
+        space = Escrow::DISCRIMINATOR.len() + Escrow::INIT_SPACE,
+        bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"nonce", maker.key().as_ref(), intent.nonce.to_le_bytes().as_ref()],
+        space = UsedNonce::DISCRIMINATOR.len() + UsedNonce::INIT_SPACE,
+        bump
+    )]
+    pub used_nonce: Account<'info, UsedNonce>,
+    /// CHECK: The instructions sysvar, checked by address
+    #[account(
+        address = solana_sdk_ids::sysvar::instructions::ID
+    )]
+    pub instructions: UncheckedAccount<'info>,
+    /// CHECK: Protocol config PDA; it may not exist yet, so it is read with `ProtocolConfig::fee_terms`
+    #[account(
+        seeds = [b"protocol"],
+        bump
+    )]
+    pub protocol: UncheckedAccount<'info>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MakeWithIntent<'info> {
+    pub fn verify_intent(&self, intent: &MakeIntent) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require_gt!(intent.deadline, now, EscrowError::IntentExpired);
+
+        verify_signed_message(&self.instructions, &intent.maker, &intent.message()?)?;
+
+        require_gt!(intent.receive, 0, EscrowError::InvalidAmount);
+        require_gt!(intent.deposit, 0, EscrowError::InvalidAmount);
+        require_gt!(intent.expires_at, now, EscrowError::InvalidExpiry);
+        intent.pricing.validate()
+    }
+
+    pub fn init_escrow(&mut self, intent: &MakeIntent, bumps: &MakeWithIntentBumps) -> Result<()> {
+        self.used_nonce.set_inner(UsedNonce {
+            maker: intent.maker,
+            nonce: intent.nonce,
+            payer: self.payer.key(),
+            deadline: intent.deadline,
+            bump: bumps.used_nonce,
+        });
+
+        let (fee_bps, referral_bps) = ProtocolConfig::fee_terms(&self.protocol)?;
+
+        self.escrow.set_inner(Escrow {
+            seed: intent.seed,
+            maker: intent.maker,
+            mint_a: intent.mint_a,
+            mint_b: intent.mint_b,
+            receive: intent.receive,
+            deposit: intent.deposit,
+            remaining: intent.deposit,
+            expires_at: intent.expires_at,
+            taker: intent.taker,
+            pricing: intent.pricing,
+            side: Side::Ask,
+            version: 0,
+            fee_bps,
+            referral_bps,
+            rent_payer: self.payer.key(),
+            vault_rent_payer: self.payer.key(),
+            bump: bumps.escrow,
+        });
+
+        emit!(EscrowMade {
+            escrow: self.escrow.key(),
+            maker: intent.maker,
+            seed: intent.seed,
+            mint_a: intent.mint_a,
+            mint_b: intent.mint_b,
+            deposit: intent.deposit,
+            receive: intent.receive,
+            expires_at: intent.expires_at,
+            taker: intent.taker,
+            pricing: intent.pricing,
+            side: Side::Ask,
+        });
+        Ok(())
+    }
+
+    pub fn deposit(&mut self, deposit: u64, bumps: &MakeWithIntentBumps) -> Result<()> {
+        let signer_seeds: &[&[&[u8]]] = &[&[b"delegate", &[bumps.delegate]]];
+
+        let transfer_accounts = TransferChecked {
+            from: self.maker_ata_a.to_account_info(),
+            mint: self.mint_a.to_account_info(),
+            to: self.vault.to_account_info(),
+            authority: self.delegate.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            transfer_accounts,
+            signer_seeds,
+        );
+
+        transfer_checked(cpi_ctx, deposit, self.mint_a.decimals)
+    }
+}
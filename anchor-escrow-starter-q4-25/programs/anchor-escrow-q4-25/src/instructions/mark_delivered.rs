@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+
+// The beneficiary hands in the work. From here the maker has `review_period`
+// seconds to release or dispute before the payment can be settled to the
+// beneficiary without them.
+
+use crate::{errors::EscrowError, ArbitratedEscrow, ArbitrationStatus};
+
+#[derive(Accounts)]
+pub struct MarkDelivered<'info> {
+    pub beneficiary: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = beneficiary @ EscrowError::InvalidBeneficiary,
+        seeds = [b"arbitrated", arbitrated.maker.as_ref(), &arbitrated.seed.to_le_bytes()],
+        bump = arbitrated.bump
+    )]
+    pub arbitrated: Account<'info, ArbitratedEscrow>,
+}
+
+impl<'info> MarkDelivered<'info> {
+    pub fn mark_delivered(&mut self) -> Result<()> {
+        require!(
+            self.arbitrated.status == ArbitrationStatus::Funded,
+            EscrowError::InvalidStatus
+        );
+
+        self.arbitrated.status = ArbitrationStatus::Delivered;
+        self.arbitrated.updated_at = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+}
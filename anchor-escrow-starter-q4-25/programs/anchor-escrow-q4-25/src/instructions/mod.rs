@@ -1,8 +1,107 @@
 pub mod make;
 pub use make::*;
 
+pub mod make_with_intent;
+pub use make_with_intent::*;
+
+pub mod close_nonce;
+pub use close_nonce::*;
+
 pub mod refund;
 pub use refund::*;
 
 pub mod take;
 pub use take::*;
+
+pub mod amend;
+pub use amend::*;
+
+pub mod take_many;
+pub use take_many::*;
+
+pub mod crank_expired;
+pub use crank_expired::*;
+
+pub mod make_basket;
+pub use make_basket::*;
+
+pub mod take_basket;
+pub use take_basket::*;
+
+pub mod refund_basket;
+pub use refund_basket::*;
+
+pub mod crank_expired_basket;
+pub use crank_expired_basket::*;
+
+pub mod make_asset_offer;
+pub use make_asset_offer::*;
+
+pub mod take_asset_offer;
+pub use take_asset_offer::*;
+
+pub mod refund_asset_offer;
+pub use refund_asset_offer::*;
+
+pub mod make_asset_bid;
+pub use make_asset_bid::*;
+
+pub mod take_asset_bid;
+pub use take_asset_bid::*;
+
+pub mod refund_asset_bid;
+pub use refund_asset_bid::*;
+
+pub mod make_arbitrated;
+pub use make_arbitrated::*;
+
+pub mod mark_delivered;
+pub use mark_delivered::*;
+
+pub mod dispute;
+pub use dispute::*;
+
+pub mod settle_arbitrated;
+pub use settle_arbitrated::*;
+
+pub mod make_milestones;
+pub use make_milestones::*;
+
+pub mod release_milestone;
+pub use release_milestone::*;
+
+pub mod dispute_milestone;
+pub use dispute_milestone::*;
+
+pub mod cancel_milestones;
+pub use cancel_milestones::*;
+
+pub mod settle_milestone;
+pub use settle_milestone::*;
+
+pub mod create_vesting;
+pub use create_vesting::*;
+
+pub mod claim_vested;
+pub use claim_vested::*;
+
+pub mod revoke_vesting;
+pub use revoke_vesting::*;
+
+pub mod create_stream;
+pub use create_stream::*;
+
+pub mod withdraw_stream;
+pub use withdraw_stream::*;
+
+pub mod top_up_stream;
+pub use top_up_stream::*;
+
+pub mod cancel_stream;
+pub use cancel_stream::*;
+
+pub mod initialize_protocol;
+pub use initialize_protocol::*;
+
+pub mod update_protocol;
+pub use update_protocol::*;
@@ -1,6 +1,6 @@
 use anchor_lang::prelude::*;
 
-use crate::Escrow;
+use crate::{errors::EscrowError, events::EscrowRefunded, Escrow};
 use anchor_spl::{
     // CPI helper for dealing with associated token accounts (ATAs).
     associated_token::AssociatedToken,
@@ -27,17 +27,23 @@ pub struct Refund<'info> {
     // The on-chain `Escrow` account that stores escrow metadata.
     //
     // - `mut`: we will write to it (Anchor will zero it on close).
-    // - `close = maker`: when this account is closed, lamports are sent to `maker`.
+    // - `close = rent_payer`: when this account is closed, lamports go back
+    //    to whoever paid for it at `make`.
     // - `has_one = mint_a` / `has_one = maker`: Anchor enforces the stored
-    //    escrow fields for safety (prevents mismatched accounts).
-    // - `seeds` / `bump`: this account is a PDA derived from the maker and seed
-    //    so we can sign CPI calls on its behalf using the PDA seeds.
+    //    escrow fields for safety (prevents mismatched accounts) and fails
+    //    with `InvalidMintA` / `InvalidMaker` respectively.
+    // - `seeds` / `bump`: this account is a PDA derived from the stored maker
+    //    and seed so we can sign CPI calls on its behalf using the PDA seeds.
+    //    Deriving from `escrow.maker` lets `has_one = maker` report a wrong
+    //    maker instead of a generic seeds mismatch.
     #[account(
         mut,
-        close = maker,
-        has_one = mint_a,
-        has_one = maker,
-        seeds = [b"escrow", maker.key().as_ref(), &escrow.seed.to_le_bytes()],
+        close = rent_payer,
+        has_one = mint_a @ EscrowError::InvalidMintA,
+        has_one = maker @ EscrowError::InvalidMaker,
+        has_one = rent_payer @ EscrowError::InvalidRentPayer,
+        has_one = vault_rent_payer @ EscrowError::InvalidRentPayer,
+        seeds = [b"escrow", escrow.maker.as_ref(), &escrow.seed.to_le_bytes()],
         bump = escrow.bump
     )]
     pub escrow: Account<'info, Escrow>,
@@ -57,6 +63,12 @@ pub struct Refund<'info> {
     )]
     pub vault: InterfaceAccount<'info, TokenAccount>,
 
+    // The accounts that paid the rent of the escrow and the vault at `make`.
+    #[account(mut)]
+    pub rent_payer: SystemAccount<'info>,
+    #[account(mut)]
+    pub vault_rent_payer: SystemAccount<'info>,
+
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
@@ -105,13 +117,19 @@ impl<'info> Refund<'info> {
         // mint's decimal to satisfy `transfer_checked`'s requirements.
         transfer_checked(tranfer_cpi_ctx, self.vault.amount, self.mint_a.decimals)?;
 
+        emit!(EscrowRefunded {
+            escrow: self.escrow.key(),
+            maker: self.maker.key(),
+            amount: self.vault.amount,
+        });
+
         // After transferring tokens out, close the vault token account. The
-        // rent-exempt lamports held by the token account will be returned to
-        // the maker (destination) because the `close` parameter on the
-        // `escrow` account was set to `maker` in the `Accounts` struct.
+        // rent-exempt lamports held by the token account are returned to
+        // whoever paid for the vault; the escrow's own rent goes to
+        // `rent_payer` through the `close` constraint.
         let close_accounts = CloseAccount {
             account: self.vault.to_account_info(),
-            destination: self.maker.to_account_info(),
+            destination: self.vault_rent_payer.to_account_info(),
             authority: self.escrow.to_account_info(),
         };
 
@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+
+// Maker takes the Core asset back out of an AssetOffer and closes it.
+
+use crate::{
+    errors::EscrowError,
+    mpl_core::{transfer_v1, CoreAsset, MPL_CORE_ID},
+    AssetOffer,
+};
+
+#[derive(Accounts)]
+pub struct RefundAssetOffer<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    /// CHECK: Core asset, must be the one stored on the offer
+    #[account(mut)]
+    pub asset: UncheckedAccount<'info>,
+    /// CHECK: Must match the asset's collection, checked in `CoreAsset::require_collection`
+    pub collection: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        mut,
+        close = maker,
+        has_one = maker @ EscrowError::InvalidMaker,
+        has_one = asset @ EscrowError::InvalidAsset,
+        seeds = [b"asset_offer", offer.maker.as_ref(), &offer.seed.to_le_bytes()],
+        bump = offer.bump
+    )]
+    pub offer: Account<'info, AssetOffer>,
+
+    /// CHECK: Metaplex Core program
+    #[account(address = MPL_CORE_ID)]
+    pub mpl_core_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> RefundAssetOffer<'info> {
+    pub fn refund_asset(&mut self) -> Result<()> {
+        CoreAsset::load(&self.asset)?.require_collection(self.collection.as_deref())?;
+
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"asset_offer",
+            self.maker.to_account_info().key.as_ref(),
+            &self.offer.seed.to_le_bytes(),
+            &[self.offer.bump],
+        ]];
+
+        transfer_v1(
+            self.asset.to_account_info(),
+            self.collection.as_ref().map(|c| c.to_account_info()),
+            self.maker.to_account_info(),
+            self.offer.to_account_info(),
+            self.maker.to_account_info(),
+            self.system_program.to_account_info(),
+            self.mpl_core_program.to_account_info(),
+            signer_seeds,
+        )
+    }
+}
@@ -0,0 +1,101 @@
+use anchor_lang::prelude::*;
+
+// Basket version of `refund`: every offered leg goes back to the maker and the
+// vaults and the basket are closed.
+
+// Remaining accounts, three per offered leg, in the order of `offered`:
+//     mint: the offered mint
+//     vault: the basket's ATA for that mint
+//     maker_ata: the maker's ATA for that mint
+
+use crate::{
+    errors::EscrowError,
+    utils::{load_mint_decimals, require_ata},
+    BasketEscrow,
+};
+use anchor_spl::token_interface::{
+    close_account, transfer_checked, CloseAccount, TokenAccount, TokenInterface, TransferChecked,
+};
+
+#[derive(Accounts)]
+pub struct RefundBasket<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(
+        mut,
+        close = maker,
+        has_one = maker @ EscrowError::InvalidMaker,
+        seeds = [b"basket", basket.maker.as_ref(), &basket.seed.to_le_bytes()],
+        bump = basket.bump
+    )]
+    pub basket: Account<'info, BasketEscrow>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> RefundBasket<'info> {
+    pub fn refund_and_close_vaults(
+        &mut self,
+        remaining_accounts: &'info [AccountInfo<'info>],
+    ) -> Result<()> {
+        require_eq!(
+            remaining_accounts.len(),
+            self.basket.offered.len() * 3,
+            EscrowError::InvalidRemainingAccounts
+        );
+
+        let token_program = self.token_program.key();
+
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"basket",
+            self.maker.to_account_info().key.as_ref(),
+            &self.basket.seed.to_le_bytes(),
+            &[self.basket.bump],
+        ]];
+
+        for (leg, accounts) in self.basket.offered.iter().zip(remaining_accounts.chunks(3)) {
+            let [mint, vault, maker_ata] = accounts else {
+                return err!(EscrowError::InvalidRemainingAccounts);
+            };
+
+            let decimals = load_mint_decimals(mint, &leg.mint, &token_program)?;
+            require_ata(vault, &self.basket.key(), &leg.mint, &token_program)?;
+            require_ata(maker_ata, &self.maker.key(), &leg.mint, &token_program)?;
+
+            let amount = InterfaceAccount::<TokenAccount>::try_from(vault)?.amount;
+
+            let transfer_accounts = TransferChecked {
+                from: vault.clone(),
+                mint: mint.clone(),
+                to: maker_ata.clone(),
+                authority: self.basket.to_account_info(),
+            };
+
+            let cpi_ctx = CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                transfer_accounts,
+                signer_seeds,
+            );
+
+            transfer_checked(cpi_ctx, amount, decimals)?;
+
+            let close_accounts = CloseAccount {
+                account: vault.clone(),
+                destination: self.maker.to_account_info(),
+                authority: self.basket.to_account_info(),
+            };
+
+            let cpi_ctx = CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                close_accounts,
+                signer_seeds,
+            );
+
+            close_account(cpi_ctx)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,159 @@
+use anchor_lang::prelude::*;
+
+// Pays out the current milestone to its recipient, either because the maker
+// approved it or because its deadline passed without a dispute. Once the
+// last milestone is paid the vault and the escrow are closed to the maker.
+
+// The accounts needed in this context are:
+
+//     authority: the maker when approving, anyone when claiming; pays for the recipient's ATA if needed
+//     maker: the user that funded the milestones and receives the rent back
+//     recipient: the recipient of the current milestone
+//     mint: the payment token
+//     recipient_ata: the recipient's token account for the payment
+//     milestones: the escrow holding the payment schedule
+//     vault: the escrow's token account holding the unreleased payments
+
+use crate::{errors::EscrowError, MilestoneEscrow};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
+        TransferChecked,
+    },
+};
+
+#[derive(Accounts)]
+pub struct ReleaseMilestone<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+    pub recipient: SystemAccount<'info>,
+
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = mint,
+        associated_token::authority = recipient,
+        associated_token::token_program = token_program
+    )]
+    pub recipient_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        has_one = maker @ EscrowError::InvalidMaker,
+        has_one = mint @ EscrowError::InvalidMintA,
+        seeds = [b"milestones", milestones.maker.as_ref(), &milestones.seed.to_le_bytes()],
+        bump = milestones.bump
+    )]
+    pub milestones: Account<'info, MilestoneEscrow>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = milestones,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ReleaseMilestone<'info> {
+    // The maker signs off on the current milestone, disputed or not.
+    pub fn approve(&mut self) -> Result<()> {
+        require_keys_eq!(
+            self.authority.key(),
+            self.maker.key(),
+            EscrowError::InvalidMaker
+        );
+        self.release_and_advance()
+    }
+
+    // Anyone can release an undisputed milestone once its deadline has passed.
+    pub fn claim(&mut self) -> Result<()> {
+        let milestone = self
+            .milestones
+            .current()
+            .ok_or(EscrowError::InvalidStatus)?;
+        require!(!milestone.disputed, EscrowError::InvalidStatus);
+        require_gte!(
+            Clock::get()?.unix_timestamp,
+            milestone.deadline,
+            EscrowError::TimeoutNotReached
+        );
+        self.release_and_advance()
+    }
+
+    fn release_and_advance(&mut self) -> Result<()> {
+        let milestone = *self
+            .milestones
+            .current()
+            .ok_or(EscrowError::InvalidStatus)?;
+        require_keys_eq!(
+            self.recipient.key(),
+            milestone.recipient,
+            EscrowError::InvalidBeneficiary
+        );
+
+        // The last milestone empties the vault, so tokens sent to it from
+        // outside the escrow cannot keep it from closing.
+        let is_last = self.milestones.next as usize + 1 == self.milestones.milestones.len();
+        let amount = if is_last {
+            self.vault.amount
+        } else {
+            milestone.amount
+        };
+
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"milestones",
+            self.maker.to_account_info().key.as_ref(),
+            &self.milestones.seed.to_le_bytes(),
+            &[self.milestones.bump],
+        ]];
+
+        let transfer_accounts = TransferChecked {
+            from: self.vault.to_account_info(),
+            mint: self.mint.to_account_info(),
+            to: self.recipient_ata.to_account_info(),
+            authority: self.milestones.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            transfer_accounts,
+            signer_seeds,
+        );
+
+        transfer_checked(cpi_ctx, amount, self.mint.decimals)?;
+
+        self.milestones.next += 1;
+
+        // Later milestones stay locked until their turn.
+        if !self.milestones.is_complete() {
+            return Ok(());
+        }
+
+        let close_accounts = CloseAccount {
+            account: self.vault.to_account_info(),
+            destination: self.maker.to_account_info(),
+            authority: self.milestones.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            close_accounts,
+            signer_seeds,
+        );
+
+        close_account(cpi_ctx)?;
+
+        self.milestones.close(self.maker.to_account_info())
+    }
+}
@@ -0,0 +1,109 @@
+use anchor_lang::prelude::*;
+
+// Claws back the unvested part of a revocable grant, along with any tokens
+// sent to the vault from outside the grant. What had vested stays claimable
+// by the beneficiary; if they had already claimed all of it, the vault and
+// the schedule are closed right away.
+
+use crate::{errors::EscrowError, VestingSchedule};
+use anchor_spl::token_interface::{
+    close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
+    TransferChecked,
+};
+
+#[derive(Accounts)]
+pub struct RevokeVesting<'info> {
+    #[account(mut)]
+    pub grantor: Signer<'info>,
+
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = grantor,
+        associated_token::token_program = token_program
+    )]
+    pub grantor_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        has_one = grantor @ EscrowError::InvalidMaker,
+        has_one = mint @ EscrowError::InvalidMintA,
+        seeds = [b"vesting", vesting.grantor.as_ref(), &vesting.seed.to_le_bytes()],
+        bump = vesting.bump
+    )]
+    pub vesting: Account<'info, VestingSchedule>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = vesting,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> RevokeVesting<'info> {
+    pub fn revoke(&mut self) -> Result<()> {
+        require!(
+            self.vesting.revocable && !self.vesting.revoked,
+            EscrowError::NotRevocable
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let vested = self.vesting.vested(now);
+        // Everything the beneficiary is not still owed goes back to the grantor.
+        let clawback = self.vault.amount - (vested - self.vesting.claimed);
+
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"vesting",
+            self.grantor.to_account_info().key.as_ref(),
+            &self.vesting.seed.to_le_bytes(),
+            &[self.vesting.bump],
+        ]];
+
+        if clawback > 0 {
+            let transfer_accounts = TransferChecked {
+                from: self.vault.to_account_info(),
+                mint: self.mint.to_account_info(),
+                to: self.grantor_ata.to_account_info(),
+                authority: self.vesting.to_account_info(),
+            };
+
+            let cpi_ctx = CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                transfer_accounts,
+                signer_seeds,
+            );
+
+            transfer_checked(cpi_ctx, clawback, self.mint.decimals)?;
+        }
+
+        self.vesting.total = vested;
+        self.vesting.revoked = true;
+
+        if !self.vesting.is_settled(now) {
+            return Ok(());
+        }
+
+        let close_accounts = CloseAccount {
+            account: self.vault.to_account_info(),
+            destination: self.grantor.to_account_info(),
+            authority: self.vesting.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            close_accounts,
+            signer_seeds,
+        );
+
+        close_account(cpi_ctx)?;
+
+        self.vesting.close(self.grantor.to_account_info())
+    }
+}
@@ -0,0 +1,170 @@
+use anchor_lang::prelude::*;
+
+// Every way an arbitrated escrow ends: the maker releases the payment, the
+// arbiter splits a disputed vault, or anyone settles a deal whose reviewer
+// or arbiter has been silent past their deadline. The vault is paid out,
+// then it and the escrow are closed back to the maker.
+
+// The accounts needed in this context are:
+
+//     authority: the maker, the arbiter or any cranker, depending on the instruction; pays for missing ATAs
+//     maker: the user that funded the escrow and receives the rent back
+//     beneficiary: the user that did the work
+//     mint: the payment token
+//     maker_ata: the maker's token account for the share they get back
+//     beneficiary_ata: the beneficiary's token account for their share
+//     arbitrated: the escrow holding the deal
+//     vault: the escrow's token account holding the payment
+
+use crate::{errors::EscrowError, ArbitratedEscrow, ArbitrationStatus, BPS_DENOMINATOR};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
+        TransferChecked,
+    },
+};
+
+#[derive(Accounts)]
+pub struct SettleArbitrated<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+    pub beneficiary: SystemAccount<'info>,
+
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = mint,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program
+    )]
+    pub maker_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = mint,
+        associated_token::authority = beneficiary,
+        associated_token::token_program = token_program
+    )]
+    pub beneficiary_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        close = maker,
+        has_one = maker @ EscrowError::InvalidMaker,
+        has_one = beneficiary @ EscrowError::InvalidBeneficiary,
+        has_one = mint @ EscrowError::InvalidMintA,
+        seeds = [b"arbitrated", arbitrated.maker.as_ref(), &arbitrated.seed.to_le_bytes()],
+        bump = arbitrated.bump
+    )]
+    pub arbitrated: Account<'info, ArbitratedEscrow>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = arbitrated,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> SettleArbitrated<'info> {
+    // The maker accepts the work, at any point before settlement.
+    pub fn release(&mut self) -> Result<()> {
+        require_keys_eq!(
+            self.authority.key(),
+            self.maker.key(),
+            EscrowError::InvalidMaker
+        );
+        self.payout_and_close_vault(BPS_DENOMINATOR as u16)
+    }
+
+    // The arbiter rules on a dispute, awarding `beneficiary_bps` of the vault
+    // to the beneficiary and the rest back to the maker.
+    pub fn resolve(&mut self, beneficiary_bps: u16) -> Result<()> {
+        require_keys_eq!(
+            self.authority.key(),
+            self.arbitrated.arbiter,
+            EscrowError::InvalidArbiter
+        );
+        require!(
+            self.arbitrated.status == ArbitrationStatus::Disputed,
+            EscrowError::InvalidStatus
+        );
+        self.payout_and_close_vault(beneficiary_bps)
+    }
+
+    // Permissionless fallback once the review or ruling period has run out.
+    pub fn settle_timeout(&mut self) -> Result<()> {
+        let beneficiary_bps = self
+            .arbitrated
+            .timeout_split(Clock::get()?.unix_timestamp)?;
+        self.payout_and_close_vault(beneficiary_bps)
+    }
+
+    fn payout_and_close_vault(&mut self, beneficiary_bps: u16) -> Result<()> {
+        let (to_beneficiary, to_maker) =
+            ArbitratedEscrow::split(self.vault.amount, beneficiary_bps)?;
+
+        self.pay(&self.beneficiary_ata, to_beneficiary)?;
+        self.pay(&self.maker_ata, to_maker)?;
+
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"arbitrated",
+            self.maker.to_account_info().key.as_ref(),
+            &self.arbitrated.seed.to_le_bytes(),
+            &[self.arbitrated.bump],
+        ]];
+
+        let close_accounts = CloseAccount {
+            account: self.vault.to_account_info(),
+            destination: self.maker.to_account_info(),
+            authority: self.arbitrated.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            close_accounts,
+            signer_seeds,
+        );
+
+        close_account(cpi_ctx)
+    }
+
+    fn pay(&self, to: &InterfaceAccount<'info, TokenAccount>, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"arbitrated",
+            self.maker.to_account_info().key.as_ref(),
+            &self.arbitrated.seed.to_le_bytes(),
+            &[self.arbitrated.bump],
+        ]];
+
+        let transfer_accounts = TransferChecked {
+            from: self.vault.to_account_info(),
+            mint: self.mint.to_account_info(),
+            to: to.to_account_info(),
+            authority: self.arbitrated.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            transfer_accounts,
+            signer_seeds,
+        );
+
+        transfer_checked(cpi_ctx, amount, self.mint.decimals)
+    }
+}
@@ -0,0 +1,184 @@
+use anchor_lang::prelude::*;
+
+// Settles a disputed milestone: the arbiter splits it between its recipient
+// and the maker, or anyone applies the fallback split once the arbiter has
+// been silent past the ruling period. The schedule then moves on to the
+// next milestone, and the vault and the escrow are closed to the maker once
+// the last one is settled.
+
+// The accounts needed in this context are:
+
+//     authority: the arbiter when resolving, anyone after the ruling period; pays for missing ATAs
+//     maker: the user that funded the milestones and receives the rent back
+//     recipient: the recipient of the disputed milestone
+//     mint: the payment token
+//     maker_ata: the maker's token account for the share they get back
+//     recipient_ata: the recipient's token account for their share
+//     milestones: the escrow holding the payment schedule
+//     vault: the escrow's token account holding the unreleased payments
+
+use crate::{errors::EscrowError, ArbitratedEscrow, MilestoneEscrow};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
+        TransferChecked,
+    },
+};
+
+#[derive(Accounts)]
+pub struct SettleMilestone<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+    pub recipient: SystemAccount<'info>,
+
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = mint,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program
+    )]
+    pub maker_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = mint,
+        associated_token::authority = recipient,
+        associated_token::token_program = token_program
+    )]
+    pub recipient_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        has_one = maker @ EscrowError::InvalidMaker,
+        has_one = mint @ EscrowError::InvalidMintA,
+        seeds = [b"milestones", milestones.maker.as_ref(), &milestones.seed.to_le_bytes()],
+        bump = milestones.bump
+    )]
+    pub milestones: Account<'info, MilestoneEscrow>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = milestones,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> SettleMilestone<'info> {
+    // The arbiter rules on the disputed milestone, awarding `recipient_bps`
+    // of it to the recipient and the rest back to the maker.
+    pub fn resolve(&mut self, recipient_bps: u16) -> Result<()> {
+        require_keys_eq!(
+            self.authority.key(),
+            self.milestones.arbiter,
+            EscrowError::InvalidArbiter
+        );
+        let milestone = self
+            .milestones
+            .current()
+            .ok_or(EscrowError::InvalidStatus)?;
+        require!(milestone.disputed, EscrowError::InvalidStatus);
+        self.split_and_advance(recipient_bps)
+    }
+
+    // Permissionless fallback once the ruling period has run out.
+    pub fn settle_timeout(&mut self) -> Result<()> {
+        let recipient_bps = self
+            .milestones
+            .timeout_split(Clock::get()?.unix_timestamp)?;
+        self.split_and_advance(recipient_bps)
+    }
+
+    fn split_and_advance(&mut self, recipient_bps: u16) -> Result<()> {
+        let milestone = *self
+            .milestones
+            .current()
+            .ok_or(EscrowError::InvalidStatus)?;
+        require_keys_eq!(
+            self.recipient.key(),
+            milestone.recipient,
+            EscrowError::InvalidBeneficiary
+        );
+
+        // As with a release, the last milestone empties the vault.
+        let is_last = self.milestones.next as usize + 1 == self.milestones.milestones.len();
+        let amount = if is_last {
+            self.vault.amount
+        } else {
+            milestone.amount
+        };
+        let (to_recipient, to_maker) = ArbitratedEscrow::split(amount, recipient_bps)?;
+
+        self.pay(&self.recipient_ata, to_recipient)?;
+        self.pay(&self.maker_ata, to_maker)?;
+
+        self.milestones.next += 1;
+
+        if !self.milestones.is_complete() {
+            return Ok(());
+        }
+
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"milestones",
+            self.maker.to_account_info().key.as_ref(),
+            &self.milestones.seed.to_le_bytes(),
+            &[self.milestones.bump],
+        ]];
+
+        let close_accounts = CloseAccount {
+            account: self.vault.to_account_info(),
+            destination: self.maker.to_account_info(),
+            authority: self.milestones.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            close_accounts,
+            signer_seeds,
+        );
+
+        close_account(cpi_ctx)?;
+
+        self.milestones.close(self.maker.to_account_info())
+    }
+
+    fn pay(&self, to: &InterfaceAccount<'info, TokenAccount>, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"milestones",
+            self.maker.to_account_info().key.as_ref(),
+            &self.milestones.seed.to_le_bytes(),
+            &[self.milestones.bump],
+        ]];
+
+        let transfer_accounts = TransferChecked {
+            from: self.vault.to_account_info(),
+            mint: self.mint.to_account_info(),
+            to: to.to_account_info(),
+            authority: self.milestones.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            transfer_accounts,
+            signer_seeds,
+        );
+
+        transfer_checked(cpi_ctx, amount, self.mint.decimals)
+    }
+}
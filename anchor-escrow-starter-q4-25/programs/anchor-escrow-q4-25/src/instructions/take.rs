@@ -11,7 +11,13 @@
 //     taker_ata_a: the token account associated with the taker and mint_a that will receive the tokens from the vault
 //     taker_ata_b: the token account associated with the taker and mint_b that will send the tokens to the maker
 //     maker_ata_b: the token account associated with the maker and mint_b that will receive the tokens to the taker
+//     rent_payer: the account that funded the escrow, refunded its rent once the offer is filled
+//     vault_rent_payer: the account that funded the vault, refunded its rent once the offer is filled
 //     associated_token_program: the associated token program used to create the associated token accounts
+//     protocol: the protocol config PDA, naming the treasury that collects the escrow's fee
+//     treasury_ata_b: the treasury's mint_b token account that receives the protocol fee
+//     referrer_ata_b: optional mint_b token account of the integrator that gets a share of the fee
+//     oracle: the price feed, only needed for oracle-priced offers
 //     token_program: the token program used to CPI the transfer
 //     system_program: the system program used to create the Escrow
 
@@ -26,7 +32,7 @@ use anchor_spl::{
     },
 };
 
-use crate::Escrow;
+use crate::{errors::EscrowError, events::EscrowTaken, Escrow, Fill, ProtocolConfig};
 
 #[derive(Accounts)]
 pub struct Take<'info> {
@@ -70,11 +76,13 @@ pub struct Take<'info> {
 
     #[account(
         mut,
-        close = maker,
-        has_one = maker,
-        has_one = mint_a,
-        has_one = mint_b,
-        seeds = [b"escrow", maker.key().as_ref(), &escrow.seed.to_le_bytes()],
+        has_one = maker @ EscrowError::InvalidMaker,
+        has_one = mint_a @ EscrowError::InvalidMintA,
+        has_one = mint_b @ EscrowError::InvalidMintB,
+        has_one = rent_payer @ EscrowError::InvalidRentPayer,
+        has_one = vault_rent_payer @ EscrowError::InvalidRentPayer,
+        constraint = escrow.taker.is_none_or(|t| t == taker.key()) @ EscrowError::InvalidTaker,
+        seeds = [b"escrow", escrow.maker.as_ref(), &escrow.seed.to_le_bytes()],
         bump = escrow.bump
     )]
     pub escrow: Account<'info, Escrow>,
@@ -85,6 +93,31 @@ pub struct Take<'info> {
         associated_token::token_program = token_program
     )]
     pub vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub rent_payer: SystemAccount<'info>,
+    #[account(mut)]
+    pub vault_rent_payer: SystemAccount<'info>,
+
+    /// CHECK: Protocol config PDA; it may not exist yet, so it is read in `protocol_config`
+    #[account(
+        seeds = [b"protocol"],
+        bump
+    )]
+    pub protocol: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        token::mint = mint_b,
+        token::token_program = token_program
+    )]
+    pub treasury_ata_b: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        token::mint = mint_b,
+        token::token_program = token_program
+    )]
+    pub referrer_ata_b: Option<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: Price feed of oracle-priced offers, matched against the escrow's pricing
+    pub oracle: Option<UncheckedAccount<'info>>,
 
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Interface<'info, TokenInterface>,
@@ -92,20 +125,104 @@ pub struct Take<'info> {
 }
 
 impl<'info> Take<'info> {
-    pub fn deposit(&mut self) -> Result<()> {
+    // Binds the taker's signature to the terms they saw, so a maker cannot
+    // refund and re-make at the same seed with different amounts underneath them.
+    // Takers that track amendments can also pin the escrow version.
+    // For oracle and auction offers `expected_receive` is the most the taker
+    // will pay for the whole deposit. Returns what the whole deposit costs now.
+    pub fn verify_terms(
+        &self,
+        expected_receive: u64,
+        expected_deposit: u64,
+        expected_version: Option<u32>,
+    ) -> Result<u64> {
+        let receive = self.escrow.current_receive(
+            self.oracle.as_deref(),
+            self.mint_a.decimals,
+            self.mint_b.decimals,
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        if self.escrow.pricing.is_fixed() {
+            require_eq!(receive, expected_receive, EscrowError::TermsChanged);
+        } else {
+            require_gte!(expected_receive, receive, EscrowError::PriceAboveLimit);
+        }
+        require_eq!(self.vault.amount, expected_deposit, EscrowError::TermsChanged);
+        if let Some(version) = expected_version {
+            require_eq!(self.escrow.version, version, EscrowError::TermsChanged);
+        }
+        Ok(receive)
+    }
+
+    // Pays the maker (minus any protocol fee) for a fill of `amount`,
+    // counted in mint_a for asks and mint_b for bids.
+    pub fn deposit(&mut self, amount: u64, receive: u64) -> Result<Fill> {
+        require_gt!(
+            self.escrow.expires_at,
+            Clock::get()?.unix_timestamp,
+            EscrowError::OfferExpired
+        );
+        require!(
+            amount > 0 && amount <= self.escrow.remaining,
+            EscrowError::InvalidAmount
+        );
+
+        let fill = self.escrow.fill_for(amount, receive)?;
+        let receive = fill.paid;
+
+        self.check_treasury()?;
+        let (fee, referral) = self
+            .escrow
+            .fee_split(receive, self.referrer_ata_b.is_some());
+
+        if fee > 0 {
+            let treasury_ata_b = self
+                .treasury_ata_b
+                .as_ref()
+                .ok_or(EscrowError::InvalidTreasury)?;
+            self.pay(treasury_ata_b, fee - referral)?;
+        }
+        if let Some(referrer_ata_b) = self.referrer_ata_b.as_ref().filter(|_| referral > 0) {
+            self.pay(referrer_ata_b, referral)?;
+        }
+
+        self.pay(&self.maker_ata_b, receive - fee)?;
+
+        Ok(fill)
+    }
+
+    fn check_treasury(&self) -> Result<()> {
+        let config = ProtocolConfig::load(&self.protocol)?;
+        if let (Some(config), Some(treasury_ata_b)) = (&config, &self.treasury_ata_b) {
+            require_keys_eq!(treasury_ata_b.owner, config.treasury, EscrowError::InvalidTreasury);
+        }
+        Ok(())
+    }
+
+    fn pay(&self, to: &InterfaceAccount<'info, TokenAccount>, amount: u64) -> Result<()> {
         let transfer_accounts = TransferChecked {
             from: self.taker_ata_b.to_account_info(),
             mint: self.mint_b.to_account_info(),
-            to: self.maker_ata_b.to_account_info(),
+            to: to.to_account_info(),
             authority: self.taker.to_account_info(),
         };
 
         let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), transfer_accounts);
 
-        transfer_checked(cpi_ctx, self.escrow.receive, self.mint_b.decimals)
+        transfer_checked(cpi_ctx, amount, self.mint_b.decimals)
     }
 
-    pub fn withdraw_and_close_vault(&mut self) -> Result<()> {
+    pub fn withdraw_and_close_vault(&mut self, amount: u64, fill: Fill) -> Result<()> {
+        // The last fill empties the vault, including any tokens sent to it
+        // from outside the escrow, so it can always be closed.
+        let withdrawn = if amount == self.escrow.remaining {
+            self.vault.reload()?;
+            self.vault.amount
+        } else {
+            fill.withdrawn
+        };
+
         let transfer_accounts = TransferChecked {
             from: self.vault.to_account_info(),
             mint: self.mint_a.to_account_info(),
@@ -126,11 +243,26 @@ impl<'info> Take<'info> {
             signer_seeds,
         );
 
-        transfer_checked(cpi_ctx, self.vault.amount, self.mint_a.decimals)?;
+        transfer_checked(cpi_ctx, withdrawn, self.mint_a.decimals)?;
+
+        self.escrow.remaining -= amount;
+
+        emit!(EscrowTaken {
+            escrow: self.escrow.key(),
+            taker: self.taker.key(),
+            amount: withdrawn,
+            paid: fill.paid,
+            remaining: self.escrow.remaining,
+        });
+
+        // Partially filled offers stay open for the next taker.
+        if self.escrow.remaining > 0 {
+            return Ok(());
+        }
 
         let close_accounts = CloseAccount {
             account: self.vault.to_account_info(),
-            destination: self.maker.to_account_info(),
+            destination: self.vault_rent_payer.to_account_info(),
             authority: self.escrow.to_account_info(),
         };
 
@@ -140,14 +272,26 @@ impl<'info> Take<'info> {
             signer_seeds,
         );
 
-        close_account(cpi_ctx)
+        close_account(cpi_ctx)?;
+
+        self.escrow.close(self.rent_payer.to_account_info())
     }
 
-    pub fn handler(ctx: Context<Take>) -> Result<()> {
-    // Transfer Token B to Maker
-    ctx.accounts.deposit()?;
-    // Withdraw and close the Vault
-    ctx.accounts.withdraw_and_close_vault()?;
-    Ok(())
-}
+    pub fn handler(
+        ctx: Context<Take>,
+        amount: u64,
+        expected_receive: u64,
+        expected_deposit: u64,
+        expected_version: Option<u32>,
+    ) -> Result<()> {
+        // Check the terms the taker signed for
+        let receive =
+            ctx.accounts
+                .verify_terms(expected_receive, expected_deposit, expected_version)?;
+        // Transfer Token B to Maker
+        let fill = ctx.accounts.deposit(amount, receive)?;
+        // Withdraw and close the Vault
+        ctx.accounts.withdraw_and_close_vault(amount, fill)?;
+        Ok(())
+    }
 }
\ No newline at end of file
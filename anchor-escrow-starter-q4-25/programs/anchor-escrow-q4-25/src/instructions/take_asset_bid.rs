@@ -0,0 +1,155 @@
+use anchor_lang::prelude::*;
+
+// Taker fills an AssetBid with a Core asset matching its target.
+// The taker transfers the asset to the maker with TransferV1, then receives
+// the vault's mint_a and the vault and bid are closed back to the maker.
+
+use crate::{
+    errors::EscrowError,
+    mpl_core::{transfer_v1, CoreAsset, MPL_CORE_ID},
+    AssetBid, CoreAssetTarget,
+};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
+        TransferChecked,
+    },
+};
+
+#[derive(Accounts)]
+pub struct TakeAssetBid<'info> {
+    #[account(mut)]
+    pub taker: Signer<'info>,
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+
+    /// CHECK: Core asset, checked against the bid target in `deliver_asset`
+    #[account(mut)]
+    pub asset: UncheckedAccount<'info>,
+    /// CHECK: Must match the asset's collection, checked in `CoreAsset::require_collection`
+    pub collection: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint_a: InterfaceAccount<'info, Mint>,
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_a,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program
+    )]
+    pub taker_ata_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        close = maker,
+        has_one = maker @ EscrowError::InvalidMaker,
+        has_one = mint_a @ EscrowError::InvalidMintA,
+        seeds = [b"asset_bid", bid.maker.as_ref(), &bid.seed.to_le_bytes()],
+        bump = bid.bump
+    )]
+    pub bid: Account<'info, AssetBid>,
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = bid,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Metaplex Core program
+    #[account(address = MPL_CORE_ID)]
+    pub mpl_core_program: UncheckedAccount<'info>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> TakeAssetBid<'info> {
+    // Binds the taker's signature to the bid they saw, so a maker cannot
+    // refund and re-make at the same seed with a smaller deposit or another target.
+    pub fn verify_terms(
+        &self,
+        expected_deposit: u64,
+        expected_target: CoreAssetTarget,
+    ) -> Result<()> {
+        require_gt!(
+            self.bid.expires_at,
+            Clock::get()?.unix_timestamp,
+            EscrowError::OfferExpired
+        );
+        require_eq!(
+            self.bid.deposit,
+            expected_deposit,
+            EscrowError::TermsChanged
+        );
+        require!(
+            self.bid.target == expected_target,
+            EscrowError::TermsChanged
+        );
+        Ok(())
+    }
+
+    pub fn deliver_asset(&mut self) -> Result<()> {
+        let asset = CoreAsset::load(&self.asset)?;
+        asset.require_collection(self.collection.as_deref())?;
+
+        let accepted = match self.bid.target {
+            CoreAssetTarget::Asset(key) => key == self.asset.key(),
+            CoreAssetTarget::Collection(key) => asset.collection == Some(key),
+        };
+        require!(accepted, EscrowError::InvalidAsset);
+
+        transfer_v1(
+            self.asset.to_account_info(),
+            self.collection.as_ref().map(|c| c.to_account_info()),
+            self.taker.to_account_info(),
+            self.taker.to_account_info(),
+            self.maker.to_account_info(),
+            self.system_program.to_account_info(),
+            self.mpl_core_program.to_account_info(),
+            &[],
+        )
+    }
+
+    pub fn withdraw_and_close_vault(&mut self) -> Result<()> {
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"asset_bid",
+            self.maker.to_account_info().key.as_ref(),
+            &self.bid.seed.to_le_bytes(),
+            &[self.bid.bump],
+        ]];
+
+        let transfer_accounts = TransferChecked {
+            from: self.vault.to_account_info(),
+            mint: self.mint_a.to_account_info(),
+            to: self.taker_ata_a.to_account_info(),
+            authority: self.bid.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            transfer_accounts,
+            signer_seeds,
+        );
+
+        transfer_checked(cpi_ctx, self.vault.amount, self.mint_a.decimals)?;
+
+        let close_accounts = CloseAccount {
+            account: self.vault.to_account_info(),
+            destination: self.maker.to_account_info(),
+            authority: self.bid.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            close_accounts,
+            signer_seeds,
+        );
+
+        close_account(cpi_ctx)
+    }
+}
@@ -0,0 +1,120 @@
+use anchor_lang::prelude::*;
+
+// Taker buys the Core asset held by an AssetOffer.
+// mint_b goes from the taker to the maker, then the offer PDA signs a
+// TransferV1 handing the asset to the taker, all in one instruction.
+
+use crate::{
+    errors::EscrowError,
+    mpl_core::{transfer_v1, CoreAsset, MPL_CORE_ID},
+    AssetOffer,
+};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+#[derive(Accounts)]
+pub struct TakeAssetOffer<'info> {
+    #[account(mut)]
+    pub taker: Signer<'info>,
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+
+    /// CHECK: Core asset, must be the one stored on the offer
+    #[account(mut)]
+    pub asset: UncheckedAccount<'info>,
+    /// CHECK: Must match the asset's collection, checked in `CoreAsset::require_collection`
+    pub collection: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint_b: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program
+    )]
+    pub taker_ata_b: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_b,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program
+    )]
+    pub maker_ata_b: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        close = maker,
+        has_one = maker @ EscrowError::InvalidMaker,
+        has_one = asset @ EscrowError::InvalidAsset,
+        has_one = mint_b @ EscrowError::InvalidMintB,
+        seeds = [b"asset_offer", offer.maker.as_ref(), &offer.seed.to_le_bytes()],
+        bump = offer.bump
+    )]
+    pub offer: Account<'info, AssetOffer>,
+
+    /// CHECK: Metaplex Core program
+    #[account(address = MPL_CORE_ID)]
+    pub mpl_core_program: UncheckedAccount<'info>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> TakeAssetOffer<'info> {
+    // Binds the taker's signature to the price they saw, so a maker cannot
+    // refund and re-make at the same seed with a higher `receive`.
+    pub fn verify_terms(&self, expected_receive: u64) -> Result<()> {
+        require_gt!(
+            self.offer.expires_at,
+            Clock::get()?.unix_timestamp,
+            EscrowError::OfferExpired
+        );
+        require_eq!(
+            self.offer.receive,
+            expected_receive,
+            EscrowError::TermsChanged
+        );
+        Ok(())
+    }
+
+    pub fn deposit(&mut self) -> Result<()> {
+        let transfer_accounts = TransferChecked {
+            from: self.taker_ata_b.to_account_info(),
+            mint: self.mint_b.to_account_info(),
+            to: self.maker_ata_b.to_account_info(),
+            authority: self.taker.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), transfer_accounts);
+
+        transfer_checked(cpi_ctx, self.offer.receive, self.mint_b.decimals)
+    }
+
+    pub fn withdraw_asset(&mut self) -> Result<()> {
+        CoreAsset::load(&self.asset)?.require_collection(self.collection.as_deref())?;
+
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"asset_offer",
+            self.maker.to_account_info().key.as_ref(),
+            &self.offer.seed.to_le_bytes(),
+            &[self.offer.bump],
+        ]];
+
+        transfer_v1(
+            self.asset.to_account_info(),
+            self.collection.as_ref().map(|c| c.to_account_info()),
+            self.taker.to_account_info(),
+            self.offer.to_account_info(),
+            self.taker.to_account_info(),
+            self.system_program.to_account_info(),
+            self.mpl_core_program.to_account_info(),
+            signer_seeds,
+        )
+    }
+}
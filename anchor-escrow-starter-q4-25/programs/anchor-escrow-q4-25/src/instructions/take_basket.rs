@@ -0,0 +1,187 @@
+use anchor_lang::prelude::*;
+
+// Basket version of `take`: the taker pays every requested leg to the maker and
+// receives every offered leg from the basket vaults, then the vaults and the
+// basket are closed with their rent going back to the maker.
+
+// Remaining accounts, three per offered leg in the order of `offered`:
+//     mint: the offered mint
+//     vault: the basket's ATA for that mint
+//     taker_ata: the taker's ATA for that mint, created if needed
+// followed by three per requested leg in the order of `requested`:
+//     mint: the requested mint
+//     taker_ata: the taker's ATA for that mint, tokens are paid out of it
+//     maker_ata: the maker's ATA for that mint, created if needed
+
+use crate::{
+    errors::EscrowError,
+    utils::{create_ata_if_needed, load_mint_decimals, require_ata},
+    BasketEscrow, BasketLeg,
+};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        close_account, transfer_checked, CloseAccount, TokenAccount, TokenInterface,
+        TransferChecked,
+    },
+};
+
+#[derive(Accounts)]
+pub struct TakeBasket<'info> {
+    #[account(mut)]
+    pub taker: Signer<'info>,
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        close = maker,
+        has_one = maker @ EscrowError::InvalidMaker,
+        seeds = [b"basket", basket.maker.as_ref(), &basket.seed.to_le_bytes()],
+        bump = basket.bump
+    )]
+    pub basket: Account<'info, BasketEscrow>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> TakeBasket<'info> {
+    // Binds the taker's signature to the legs they saw, so a maker cannot
+    // refund and re-make at the same seed with different legs underneath them.
+    pub fn verify_terms(
+        &self,
+        expected_offered: &[BasketLeg],
+        expected_requested: &[BasketLeg],
+    ) -> Result<()> {
+        require_gt!(
+            self.basket.expires_at,
+            Clock::get()?.unix_timestamp,
+            EscrowError::OfferExpired
+        );
+        require!(
+            self.basket.offered == expected_offered && self.basket.requested == expected_requested,
+            EscrowError::TermsChanged
+        );
+        Ok(())
+    }
+
+    pub fn deposit(&mut self, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+        let offered_accounts = self.basket.offered.len() * 3;
+        require_eq!(
+            remaining_accounts.len(),
+            offered_accounts + self.basket.requested.len() * 3,
+            EscrowError::InvalidRemainingAccounts
+        );
+
+        let token_program = self.token_program.key();
+
+        for (leg, accounts) in self
+            .basket
+            .requested
+            .iter()
+            .zip(remaining_accounts[offered_accounts..].chunks(3))
+        {
+            let [mint, taker_ata, maker_ata] = accounts else {
+                return err!(EscrowError::InvalidRemainingAccounts);
+            };
+
+            let decimals = load_mint_decimals(mint, &leg.mint, &token_program)?;
+            require_ata(taker_ata, &self.taker.key(), &leg.mint, &token_program)?;
+            require_ata(maker_ata, &self.maker.key(), &leg.mint, &token_program)?;
+
+            create_ata_if_needed(
+                self.taker.to_account_info(),
+                maker_ata.clone(),
+                self.maker.to_account_info(),
+                mint.clone(),
+                self.system_program.to_account_info(),
+                self.token_program.to_account_info(),
+                self.associated_token_program.to_account_info(),
+            )?;
+
+            let transfer_accounts = TransferChecked {
+                from: taker_ata.clone(),
+                mint: mint.clone(),
+                to: maker_ata.clone(),
+                authority: self.taker.to_account_info(),
+            };
+
+            let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), transfer_accounts);
+
+            transfer_checked(cpi_ctx, leg.amount, decimals)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn withdraw_and_close_vaults(
+        &mut self,
+        remaining_accounts: &'info [AccountInfo<'info>],
+    ) -> Result<()> {
+        let token_program = self.token_program.key();
+
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"basket",
+            self.maker.to_account_info().key.as_ref(),
+            &self.basket.seed.to_le_bytes(),
+            &[self.basket.bump],
+        ]];
+
+        for (leg, accounts) in self.basket.offered.iter().zip(remaining_accounts.chunks(3)) {
+            let [mint, vault, taker_ata] = accounts else {
+                return err!(EscrowError::InvalidRemainingAccounts);
+            };
+
+            let decimals = load_mint_decimals(mint, &leg.mint, &token_program)?;
+            require_ata(vault, &self.basket.key(), &leg.mint, &token_program)?;
+            require_ata(taker_ata, &self.taker.key(), &leg.mint, &token_program)?;
+
+            create_ata_if_needed(
+                self.taker.to_account_info(),
+                taker_ata.clone(),
+                self.taker.to_account_info(),
+                mint.clone(),
+                self.system_program.to_account_info(),
+                self.token_program.to_account_info(),
+                self.associated_token_program.to_account_info(),
+            )?;
+
+            // Send the whole balance, not just `leg.amount`, so tokens sent to
+            // the vault from outside the basket cannot keep it from closing.
+            let amount = InterfaceAccount::<TokenAccount>::try_from(vault)?.amount;
+
+            let transfer_accounts = TransferChecked {
+                from: vault.clone(),
+                mint: mint.clone(),
+                to: taker_ata.clone(),
+                authority: self.basket.to_account_info(),
+            };
+
+            let cpi_ctx = CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                transfer_accounts,
+                signer_seeds,
+            );
+
+            transfer_checked(cpi_ctx, amount, decimals)?;
+
+            let close_accounts = CloseAccount {
+                account: vault.clone(),
+                destination: self.maker.to_account_info(),
+                authority: self.basket.to_account_info(),
+            };
+
+            let cpi_ctx = CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                close_accounts,
+                signer_seeds,
+            );
+
+            close_account(cpi_ctx)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,280 @@
+use anchor_lang::prelude::*;
+
+// Sweeps several asks for the same pair in one instruction. Escrows are
+// filled in the order given, each as far as the remaining mint_b budget
+// allows, and fully filled escrows are closed back to their rent payers. The
+// whole sweep fails if the taker ends up with less than `min_received`.
+//
+// Oracle-priced asks need their feed and are not supported here; fixed and
+// auction asks are priced exactly as `take` would price them.
+
+// Remaining accounts, six per escrow in fill order:
+//     escrow: the escrow being filled
+//     vault: the escrow's ATA for mint_a
+//     maker: the escrow's maker
+//     maker_ata_b: the maker's ATA for mint_b, created if needed
+//     rent_payer: the escrow's rent payer, refunded once the escrow is filled
+//     vault_rent_payer: the vault's rent payer, refunded once the escrow is filled
+
+use crate::{
+    errors::EscrowError,
+    events::EscrowTaken,
+    utils::{create_ata_if_needed, require_ata},
+    Escrow, ProtocolConfig, Side,
+};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
+        TransferChecked,
+    },
+};
+
+#[derive(Accounts)]
+pub struct TakeMany<'info> {
+    #[account(mut)]
+    pub taker: Signer<'info>,
+
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint_a: InterfaceAccount<'info, Mint>,
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint_b: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_a,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program
+    )]
+    pub taker_ata_a: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program
+    )]
+    pub taker_ata_b: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Protocol config PDA; it may not exist yet, so it is read with `ProtocolConfig::load`
+    #[account(
+        seeds = [b"protocol"],
+        bump
+    )]
+    pub protocol: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        token::mint = mint_b,
+        token::token_program = token_program
+    )]
+    pub treasury_ata_b: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        token::mint = mint_b,
+        token::token_program = token_program
+    )]
+    pub referrer_ata_b: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> TakeMany<'info> {
+    pub fn fill_all(
+        &mut self,
+        remaining_accounts: &'info [AccountInfo<'info>],
+        max_paid: u64,
+        min_received: u64,
+    ) -> Result<()> {
+        require!(
+            !remaining_accounts.is_empty() && remaining_accounts.len() % 6 == 0,
+            EscrowError::InvalidRemainingAccounts
+        );
+
+        let config = ProtocolConfig::load(&self.protocol)?;
+        if let (Some(config), Some(treasury_ata_b)) = (&config, &self.treasury_ata_b) {
+            require_keys_eq!(
+                treasury_ata_b.owner,
+                config.treasury,
+                EscrowError::InvalidTreasury
+            );
+        }
+
+        let now = Clock::get()?.unix_timestamp;
+        let mut paid = 0;
+        let mut received = 0;
+
+        for accounts in remaining_accounts.chunks(6) {
+            let [escrow, vault, maker, maker_ata_b, rent_payer, vault_rent_payer] = accounts else {
+                return err!(EscrowError::InvalidRemainingAccounts);
+            };
+
+            let (filled, cost) = self.fill(
+                escrow,
+                vault,
+                maker,
+                maker_ata_b,
+                rent_payer,
+                vault_rent_payer,
+                max_paid - paid,
+                now,
+            )?;
+            paid += cost;
+            received += filled;
+
+            if paid == max_paid {
+                break;
+            }
+        }
+
+        require_gte!(received, min_received, EscrowError::InsufficientFill);
+        Ok(())
+    }
+
+    // Fills one escrow as far as `budget` allows and returns the mint_a
+    // received and the mint_b paid.
+    #[allow(clippy::too_many_arguments)]
+    fn fill(
+        &self,
+        escrow_info: &'info AccountInfo<'info>,
+        vault: &'info AccountInfo<'info>,
+        maker: &'info AccountInfo<'info>,
+        maker_ata_b: &'info AccountInfo<'info>,
+        rent_payer: &'info AccountInfo<'info>,
+        vault_rent_payer: &'info AccountInfo<'info>,
+        budget: u64,
+        now: i64,
+    ) -> Result<(u64, u64)> {
+        let token_program = self.token_program.key();
+        let mut escrow = Account::<Escrow>::try_from(escrow_info)?;
+
+        require_keys_eq!(escrow.maker, maker.key(), EscrowError::InvalidMaker);
+        require_keys_eq!(
+            escrow.rent_payer,
+            rent_payer.key(),
+            EscrowError::InvalidRentPayer
+        );
+        require_keys_eq!(
+            escrow.vault_rent_payer,
+            vault_rent_payer.key(),
+            EscrowError::InvalidRentPayer
+        );
+        require_keys_eq!(escrow.mint_a, self.mint_a.key(), EscrowError::InvalidMintA);
+        require_keys_eq!(escrow.mint_b, self.mint_b.key(), EscrowError::InvalidMintB);
+        require!(escrow.side == Side::Ask, EscrowError::InvalidSide);
+        require!(
+            escrow.taker.is_none_or(|t| t == self.taker.key()),
+            EscrowError::InvalidTaker
+        );
+        require_gt!(escrow.expires_at, now, EscrowError::OfferExpired);
+        require_ata(vault, &escrow.key(), &escrow.mint_a, &token_program)?;
+        require_ata(maker_ata_b, &escrow.maker, &escrow.mint_b, &token_program)?;
+
+        let receive =
+            escrow.current_receive(None, self.mint_a.decimals, self.mint_b.decimals, now)?;
+        let amount = escrow.max_fill(budget, receive);
+        if amount == 0 {
+            return Ok((0, 0));
+        }
+        let fill = escrow.fill_for(amount, receive)?;
+
+        create_ata_if_needed(
+            self.taker.to_account_info(),
+            maker_ata_b.clone(),
+            maker.clone(),
+            self.mint_b.to_account_info(),
+            self.system_program.to_account_info(),
+            self.token_program.to_account_info(),
+            self.associated_token_program.to_account_info(),
+        )?;
+
+        let (fee, referral) = escrow.fee_split(fill.paid, self.referrer_ata_b.is_some());
+        if fee > 0 {
+            let treasury_ata_b = self
+                .treasury_ata_b
+                .as_ref()
+                .ok_or(EscrowError::InvalidTreasury)?;
+            self.pay(treasury_ata_b.to_account_info(), fee - referral)?;
+        }
+        if let Some(referrer_ata_b) = self.referrer_ata_b.as_ref().filter(|_| referral > 0) {
+            self.pay(referrer_ata_b.to_account_info(), referral)?;
+        }
+        self.pay(maker_ata_b.clone(), fill.paid - fee)?;
+
+        // The last fill empties the vault, including any tokens sent to it
+        // from outside the escrow, so it can always be closed.
+        let withdrawn = if amount == escrow.remaining {
+            InterfaceAccount::<TokenAccount>::try_from(vault)?.amount
+        } else {
+            fill.withdrawn
+        };
+
+        let seed = escrow.seed.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[b"escrow", maker.key.as_ref(), &seed, &[escrow.bump]]];
+
+        let transfer_accounts = TransferChecked {
+            from: vault.clone(),
+            mint: self.mint_a.to_account_info(),
+            to: self.taker_ata_a.to_account_info(),
+            authority: escrow_info.clone(),
+        };
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            transfer_accounts,
+            signer_seeds,
+        );
+
+        transfer_checked(cpi_ctx, withdrawn, self.mint_a.decimals)?;
+
+        escrow.remaining -= amount;
+
+        emit!(EscrowTaken {
+            escrow: escrow.key(),
+            taker: self.taker.key(),
+            amount: withdrawn,
+            paid: fill.paid,
+            remaining: escrow.remaining,
+        });
+
+        if escrow.remaining > 0 {
+            escrow.exit(&crate::ID)?;
+            return Ok((withdrawn, fill.paid));
+        }
+
+        let close_accounts = CloseAccount {
+            account: vault.clone(),
+            destination: vault_rent_payer.clone(),
+            authority: escrow_info.clone(),
+        };
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            close_accounts,
+            signer_seeds,
+        );
+
+        close_account(cpi_ctx)?;
+
+        escrow.close(rent_payer.clone())?;
+        Ok((withdrawn, fill.paid))
+    }
+
+    fn pay(&self, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        let transfer_accounts = TransferChecked {
+            from: self.taker_ata_b.to_account_info(),
+            mint: self.mint_b.to_account_info(),
+            to,
+            authority: self.taker.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), transfer_accounts);
+
+        transfer_checked(cpi_ctx, amount, self.mint_b.decimals)
+    }
+}
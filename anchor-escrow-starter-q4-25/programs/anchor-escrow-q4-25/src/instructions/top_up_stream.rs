@@ -0,0 +1,68 @@
+use anchor_lang::prelude::*;
+
+// The sender adds to the stream's balance. Time the stream spent dry is
+// earned retroactively once the balance covers it.
+
+use crate::{errors::EscrowError, PaymentStream};
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+#[derive(Accounts)]
+pub struct TopUpStream<'info> {
+    pub sender: Signer<'info>,
+
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = sender,
+        associated_token::token_program = token_program
+    )]
+    pub sender_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        has_one = sender @ EscrowError::InvalidMaker,
+        has_one = mint @ EscrowError::InvalidMintA,
+        seeds = [b"stream", stream.sender.as_ref(), &stream.seed.to_le_bytes()],
+        bump = stream.bump
+    )]
+    pub stream: Account<'info, PaymentStream>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = stream,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> TopUpStream<'info> {
+    pub fn top_up(&mut self, amount: u64) -> Result<()> {
+        require_gt!(amount, 0, EscrowError::InvalidAmount);
+
+        let transfer_accounts = TransferChecked {
+            from: self.sender_ata.to_account_info(),
+            mint: self.mint.to_account_info(),
+            to: self.vault.to_account_info(),
+            authority: self.sender.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), transfer_accounts);
+
+        transfer_checked(cpi_ctx, amount, self.mint.decimals)?;
+
+        self.stream.deposited = self
+            .stream
+            .deposited
+            .checked_add(amount)
+            .ok_or(EscrowError::InvalidAmount)?;
+        Ok(())
+    }
+}
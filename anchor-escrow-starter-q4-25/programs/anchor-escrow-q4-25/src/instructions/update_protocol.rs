@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+
+// Lets the protocol authority change the treasury and fee split.
+// Open escrows keep the fee they were made with; only the new treasury
+// applies to them.
+
+use crate::{errors::EscrowError, ProtocolConfig};
+
+#[derive(Accounts)]
+pub struct UpdateProtocol<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = authority @ EscrowError::InvalidAuthority,
+        seeds = [b"protocol"],
+        bump = protocol.bump
+    )]
+    pub protocol: Account<'info, ProtocolConfig>,
+}
+
+impl<'info> UpdateProtocol<'info> {
+    pub fn update_protocol(
+        &mut self,
+        treasury: Pubkey,
+        fee_bps: u16,
+        referral_bps: u16,
+    ) -> Result<()> {
+        ProtocolConfig::validate(fee_bps, referral_bps)?;
+
+        self.protocol.treasury = treasury;
+        self.protocol.fee_bps = fee_bps;
+        self.protocol.referral_bps = referral_bps;
+        Ok(())
+    }
+}
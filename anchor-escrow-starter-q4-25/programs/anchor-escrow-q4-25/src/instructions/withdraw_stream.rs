@@ -0,0 +1,80 @@
+use anchor_lang::prelude::*;
+
+// The recipient takes out everything the stream has earned so far.
+
+use crate::{errors::EscrowError, PaymentStream};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+#[derive(Accounts)]
+pub struct WithdrawStream<'info> {
+    #[account(mut)]
+    pub recipient: Signer<'info>,
+
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init_if_needed,
+        payer = recipient,
+        associated_token::mint = mint,
+        associated_token::authority = recipient,
+        associated_token::token_program = token_program
+    )]
+    pub recipient_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        has_one = recipient @ EscrowError::InvalidBeneficiary,
+        has_one = mint @ EscrowError::InvalidMintA,
+        seeds = [b"stream", stream.sender.as_ref(), &stream.seed.to_le_bytes()],
+        bump = stream.bump
+    )]
+    pub stream: Account<'info, PaymentStream>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = stream,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> WithdrawStream<'info> {
+    pub fn withdraw(&mut self) -> Result<()> {
+        let amount = self.stream.withdrawable(Clock::get()?.unix_timestamp);
+        require_gt!(amount, 0, EscrowError::NothingToClaim);
+
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"stream",
+            self.stream.sender.as_ref(),
+            &self.stream.seed.to_le_bytes(),
+            &[self.stream.bump],
+        ]];
+
+        let transfer_accounts = TransferChecked {
+            from: self.vault.to_account_info(),
+            mint: self.mint.to_account_info(),
+            to: self.recipient_ata.to_account_info(),
+            authority: self.stream.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            transfer_accounts,
+            signer_seeds,
+        );
+
+        transfer_checked(cpi_ctx, amount, self.mint.decimals)?;
+
+        self.stream.withdrawn += amount;
+        Ok(())
+    }
+}
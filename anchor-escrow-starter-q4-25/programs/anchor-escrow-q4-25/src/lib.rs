@@ -3,26 +3,336 @@ use anchor_lang::prelude::*;
 pub mod instructions;
 pub mod state;
 pub mod errors;
+pub mod events;
+pub mod ed25519;
+pub mod mpl_core;
+pub mod oracle;
+pub mod utils;
 pub use instructions::*;
 pub use state::*;
 pub use errors::*;
+pub use events::*;
 
 declare_id!("5UFZzEt5vU9fxtUAgsD11z63ApZEHJ5bH7Z4QpFwZ2CQ");
 
 #[program]
 pub mod anchor_escrow_q4_25 {
     use super::*;
-    pub fn make(ctx: Context<Make>, seed: u64, deposit: u64, receive: u64) -> Result<()> {
-        ctx.accounts.deposit(deposit)?;
-        ctx.accounts.init_escrow(seed, receive, &ctx.bumps)
+    pub fn make(
+        ctx: Context<Make>,
+        seed: u64,
+        deposit: u64,
+        receive: u64,
+        expires_at: i64,
+        taker: Option<Pubkey>,
+        pricing: Pricing,
+    ) -> Result<()> {
+        Make::handler(ctx, seed, deposit, receive, expires_at, taker, pricing)
+    }
+
+    pub fn make_bid(
+        ctx: Context<Make>,
+        seed: u64,
+        deposit: u64,
+        receive: u64,
+        expires_at: i64,
+        taker: Option<Pubkey>,
+    ) -> Result<()> {
+        Make::bid_handler(ctx, seed, deposit, receive, expires_at, taker)
+    }
+
+    pub fn make_with_intent(ctx: Context<MakeWithIntent>, intent: MakeIntent) -> Result<()> {
+        ctx.accounts.verify_intent(&intent)?;
+        ctx.accounts.init_escrow(&intent, &ctx.bumps)?;
+        ctx.accounts.deposit(intent.deposit, &ctx.bumps)
+    }
+
+    pub fn close_nonce(ctx: Context<CloseNonce>) -> Result<()> {
+        ctx.accounts.require_expired()
     }
 
     pub fn refund(ctx: Context<Refund>) -> Result<()> {
         ctx.accounts.refund_and_close_vault()
     }
 
-    pub fn take(ctx: Context<Take>) -> Result<()> {
+    pub fn take(
+        ctx: Context<Take>,
+        amount: u64,
+        expected_receive: u64,
+        expected_deposit: u64,
+        expected_version: Option<u32>,
+    ) -> Result<()> {
+        Take::handler(
+            ctx,
+            amount,
+            expected_receive,
+            expected_deposit,
+            expected_version,
+        )
+    }
+
+    pub fn take_many<'info>(
+        ctx: Context<'_, '_, 'info, 'info, TakeMany<'info>>,
+        max_paid: u64,
+        min_received: u64,
+    ) -> Result<()> {
+        ctx.accounts
+            .fill_all(ctx.remaining_accounts, max_paid, min_received)
+    }
+
+    pub fn amend(ctx: Context<Amend>, deposit: u64, receive: u64) -> Result<()> {
+        Amend::handler(ctx, deposit, receive)
+    }
+
+    pub fn crank_expired(ctx: Context<CrankExpired>) -> Result<()> {
+        ctx.accounts.return_and_close_vault()
+    }
+
+    pub fn initialize_protocol(
+        ctx: Context<InitializeProtocol>,
+        treasury: Pubkey,
+        fee_bps: u16,
+        referral_bps: u16,
+    ) -> Result<()> {
+        ctx.accounts
+            .init_protocol(treasury, fee_bps, referral_bps, &ctx.bumps)
+    }
+
+    pub fn update_protocol(
+        ctx: Context<UpdateProtocol>,
+        treasury: Pubkey,
+        fee_bps: u16,
+        referral_bps: u16,
+    ) -> Result<()> {
+        ctx.accounts.update_protocol(treasury, fee_bps, referral_bps)
+    }
+
+    pub fn make_basket<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MakeBasket<'info>>,
+        seed: u64,
+        offered: Vec<BasketLeg>,
+        requested: Vec<BasketLeg>,
+        expires_at: i64,
+    ) -> Result<()> {
+        ctx.accounts
+            .init_basket(seed, offered, requested, expires_at, &ctx.bumps)?;
+        ctx.accounts.deposit(ctx.remaining_accounts)
+    }
+
+    pub fn take_basket<'info>(
+        ctx: Context<'_, '_, 'info, 'info, TakeBasket<'info>>,
+        expected_offered: Vec<BasketLeg>,
+        expected_requested: Vec<BasketLeg>,
+    ) -> Result<()> {
+        ctx.accounts
+            .verify_terms(&expected_offered, &expected_requested)?;
+        ctx.accounts.deposit(ctx.remaining_accounts)?;
+        ctx.accounts.withdraw_and_close_vaults(ctx.remaining_accounts)
+    }
+
+    pub fn refund_basket<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RefundBasket<'info>>,
+    ) -> Result<()> {
+        ctx.accounts.refund_and_close_vaults(ctx.remaining_accounts)
+    }
+
+    pub fn crank_expired_basket<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CrankExpiredBasket<'info>>,
+    ) -> Result<()> {
+        ctx.accounts.return_and_close_vaults(ctx.remaining_accounts)
+    }
+
+    pub fn make_asset_offer(
+        ctx: Context<MakeAssetOffer>,
+        seed: u64,
+        receive: u64,
+        expires_at: i64,
+    ) -> Result<()> {
+        ctx.accounts.init_offer(seed, receive, expires_at, &ctx.bumps)?;
+        ctx.accounts.deposit_asset()
+    }
+
+    pub fn take_asset_offer(ctx: Context<TakeAssetOffer>, expected_receive: u64) -> Result<()> {
+        ctx.accounts.verify_terms(expected_receive)?;
         ctx.accounts.deposit()?;
+        ctx.accounts.withdraw_asset()
+    }
+
+    pub fn refund_asset_offer(ctx: Context<RefundAssetOffer>) -> Result<()> {
+        ctx.accounts.refund_asset()
+    }
+
+    pub fn make_asset_bid(
+        ctx: Context<MakeAssetBid>,
+        seed: u64,
+        deposit: u64,
+        target: CoreAssetTarget,
+        expires_at: i64,
+    ) -> Result<()> {
+        ctx.accounts
+            .init_bid(seed, deposit, target, expires_at, &ctx.bumps)?;
+        ctx.accounts.deposit(deposit)
+    }
+
+    pub fn take_asset_bid(
+        ctx: Context<TakeAssetBid>,
+        expected_deposit: u64,
+        expected_target: CoreAssetTarget,
+    ) -> Result<()> {
+        ctx.accounts.verify_terms(expected_deposit, expected_target)?;
+        ctx.accounts.deliver_asset()?;
         ctx.accounts.withdraw_and_close_vault()
     }
+
+    pub fn refund_asset_bid(ctx: Context<RefundAssetBid>) -> Result<()> {
+        ctx.accounts.refund_and_close_vault()
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn make_arbitrated(
+        ctx: Context<MakeArbitrated>,
+        seed: u64,
+        amount: u64,
+        beneficiary: Pubkey,
+        arbiter: Pubkey,
+        review_period: u32,
+        ruling_period: u32,
+        fallback_bps: u16,
+    ) -> Result<()> {
+        ctx.accounts.init_arbitrated(
+            seed,
+            amount,
+            beneficiary,
+            arbiter,
+            review_period,
+            ruling_period,
+            fallback_bps,
+            &ctx.bumps,
+        )?;
+        ctx.accounts.deposit(amount)
+    }
+
+    pub fn mark_delivered(ctx: Context<MarkDelivered>) -> Result<()> {
+        ctx.accounts.mark_delivered()
+    }
+
+    pub fn dispute(ctx: Context<Dispute>) -> Result<()> {
+        ctx.accounts.dispute()
+    }
+
+    pub fn release(ctx: Context<SettleArbitrated>) -> Result<()> {
+        ctx.accounts.release()
+    }
+
+    pub fn resolve_dispute(ctx: Context<SettleArbitrated>, beneficiary_bps: u16) -> Result<()> {
+        ctx.accounts.resolve(beneficiary_bps)
+    }
+
+    pub fn settle_timeout(ctx: Context<SettleArbitrated>) -> Result<()> {
+        ctx.accounts.settle_timeout()
+    }
+
+    pub fn make_milestones(
+        ctx: Context<MakeMilestones>,
+        seed: u64,
+        milestones: Vec<MilestoneTerms>,
+        arbiter: Pubkey,
+        ruling_period: u32,
+        fallback_bps: u16,
+    ) -> Result<()> {
+        let total = ctx.accounts.init_milestones(
+            seed,
+            milestones,
+            arbiter,
+            ruling_period,
+            fallback_bps,
+            &ctx.bumps,
+        )?;
+        ctx.accounts.deposit(total)
+    }
+
+    pub fn approve_milestone(ctx: Context<ReleaseMilestone>) -> Result<()> {
+        ctx.accounts.approve()
+    }
+
+    pub fn claim_milestone(ctx: Context<ReleaseMilestone>) -> Result<()> {
+        ctx.accounts.claim()
+    }
+
+    pub fn dispute_milestone(ctx: Context<DisputeMilestone>) -> Result<()> {
+        ctx.accounts.dispute()
+    }
+
+    pub fn cancel_milestones(ctx: Context<CancelMilestones>) -> Result<()> {
+        ctx.accounts.refund_later_milestones()
+    }
+
+    pub fn resolve_milestone(ctx: Context<SettleMilestone>, recipient_bps: u16) -> Result<()> {
+        ctx.accounts.resolve(recipient_bps)
+    }
+
+    pub fn settle_milestone_timeout(ctx: Context<SettleMilestone>) -> Result<()> {
+        ctx.accounts.settle_timeout()
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_vesting(
+        ctx: Context<CreateVesting>,
+        seed: u64,
+        beneficiary: Pubkey,
+        total: u64,
+        start: i64,
+        cliff: u32,
+        duration: u32,
+        period: u32,
+        revocable: bool,
+    ) -> Result<()> {
+        ctx.accounts.init_vesting(
+            seed,
+            beneficiary,
+            total,
+            start,
+            cliff,
+            duration,
+            period,
+            revocable,
+            &ctx.bumps,
+        )?;
+        ctx.accounts.deposit(total)
+    }
+
+    pub fn claim_vested(ctx: Context<ClaimVested>) -> Result<()> {
+        ctx.accounts.claim()
+    }
+
+    pub fn revoke_vesting(ctx: Context<RevokeVesting>) -> Result<()> {
+        ctx.accounts.revoke()
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_stream(
+        ctx: Context<CreateStream>,
+        seed: u64,
+        recipient: Pubkey,
+        rate: u64,
+        start: i64,
+        stop: i64,
+        deposit: u64,
+    ) -> Result<()> {
+        ctx.accounts
+            .init_stream(seed, recipient, rate, start, stop, deposit, &ctx.bumps)?;
+        ctx.accounts.deposit(deposit)
+    }
+
+    pub fn withdraw_stream(ctx: Context<WithdrawStream>) -> Result<()> {
+        ctx.accounts.withdraw()
+    }
+
+    pub fn top_up_stream(ctx: Context<TopUpStream>, amount: u64) -> Result<()> {
+        ctx.accounts.top_up(amount)
+    }
+
+    pub fn cancel_stream(ctx: Context<CancelStream>) -> Result<()> {
+        ctx.accounts.settle_and_close_vault()
+    }
 }
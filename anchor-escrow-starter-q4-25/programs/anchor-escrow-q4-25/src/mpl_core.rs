@@ -0,0 +1,102 @@
+use anchor_lang::prelude::*;
+
+// Minimal Metaplex Core support: reading the owner and collection of an
+// AssetV1 account and CPI-ing into `TransferV1`. Only the fields and the
+// instruction the escrow needs are covered.
+
+use crate::errors::EscrowError;
+use anchor_lang::solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program::invoke_signed,
+};
+
+pub const MPL_CORE_ID: Pubkey = pubkey!("CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d");
+
+// `Key::AssetV1` account discriminator and `MplAssetInstruction::TransferV1` index.
+const ASSET_V1_KEY: u8 = 1;
+const TRANSFER_V1: u8 = 14;
+
+// `UpdateAuthority::Collection` tag in the asset's update authority enum.
+const UPDATE_AUTHORITY_COLLECTION: u8 = 2;
+
+pub struct CoreAsset {
+    pub owner: Pubkey,
+    pub collection: Option<Pubkey>,
+}
+
+impl CoreAsset {
+    // AssetV1 layout: key (u8) | owner (Pubkey) | update_authority (u8 tag + Pubkey) | ...
+    pub fn load(account: &AccountInfo) -> Result<Self> {
+        require_keys_eq!(*account.owner, MPL_CORE_ID, EscrowError::InvalidAsset);
+
+        let data = account.try_borrow_data()?;
+        require!(
+            data.len() >= 66 && data[0] == ASSET_V1_KEY,
+            EscrowError::InvalidAsset
+        );
+
+        let owner = Pubkey::new_from_array(data[1..33].try_into().unwrap());
+        let collection = match data[33] {
+            UPDATE_AUTHORITY_COLLECTION => {
+                Some(Pubkey::new_from_array(data[34..66].try_into().unwrap()))
+            }
+            _ => None,
+        };
+
+        Ok(Self { owner, collection })
+    }
+
+    // Core rejects transfers of collection assets unless the collection is passed in.
+    pub fn require_collection(&self, collection: Option<&AccountInfo>) -> Result<()> {
+        require!(
+            self.collection == collection.map(|c| c.key()),
+            EscrowError::InvalidCollection
+        );
+        Ok(())
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_v1<'info>(
+    asset: AccountInfo<'info>,
+    collection: Option<AccountInfo<'info>>,
+    payer: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    new_owner: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    mpl_core_program: AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    // Optional accounts that are left out are passed as the Core program id.
+    let collection = collection.unwrap_or_else(|| mpl_core_program.clone());
+
+    let ix = Instruction {
+        program_id: MPL_CORE_ID,
+        accounts: vec![
+            AccountMeta::new(asset.key(), false),
+            AccountMeta::new_readonly(collection.key(), false),
+            AccountMeta::new(payer.key(), true),
+            AccountMeta::new_readonly(authority.key(), true),
+            AccountMeta::new_readonly(new_owner.key(), false),
+            AccountMeta::new_readonly(system_program.key(), false),
+            AccountMeta::new_readonly(MPL_CORE_ID, false),
+        ],
+        // TransferV1Args { compression_proof: None }
+        data: vec![TRANSFER_V1, 0],
+    };
+
+    invoke_signed(
+        &ix,
+        &[
+            asset,
+            collection,
+            payer,
+            authority,
+            new_owner,
+            system_program,
+            mpl_core_program,
+        ],
+        signer_seeds,
+    )
+    .map_err(Into::into)
+}
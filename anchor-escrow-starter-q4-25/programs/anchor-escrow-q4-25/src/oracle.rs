@@ -0,0 +1,107 @@
+use anchor_lang::prelude::*;
+
+// Minimal reader for Pyth's legacy (v2) price account layout. Only the
+// aggregate price, its confidence, exponent, status and publish time are
+// read, so any account with the same layout (such as a local mock) works.
+//
+// The owner is deliberately not checked: the maker picks the feed when the
+// offer is made and the taker bounds what they pay with `expected_receive`.
+
+use crate::errors::EscrowError;
+
+const MAGIC: u32 = 0xa1b2_c3d4;
+const VERSION: u32 = 2;
+const ACCOUNT_TYPE_PRICE: u32 = 3;
+const STATUS_TRADING: u32 = 1;
+
+const EXPO_OFFSET: usize = 20;
+const TIMESTAMP_OFFSET: usize = 96;
+const AGG_PRICE_OFFSET: usize = 208;
+const AGG_CONF_OFFSET: usize = 216;
+const AGG_STATUS_OFFSET: usize = 224;
+const PRICE_ACCOUNT_LEN: usize = 240;
+
+pub struct OraclePrice {
+    pub price: i64,
+    pub conf: u64,
+    pub expo: i32,
+    pub publish_time: i64,
+}
+
+impl OraclePrice {
+    pub fn load(account: &AccountInfo) -> Result<Self> {
+        let data = account.try_borrow_data()?;
+        require!(data.len() >= PRICE_ACCOUNT_LEN, EscrowError::InvalidOracle);
+
+        let u32_at =
+            |offset: usize| u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap());
+        let u64_at =
+            |offset: usize| u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap());
+
+        require!(
+            u32_at(0) == MAGIC && u32_at(4) == VERSION && u32_at(8) == ACCOUNT_TYPE_PRICE,
+            EscrowError::InvalidOracle
+        );
+        require_eq!(
+            u32_at(AGG_STATUS_OFFSET),
+            STATUS_TRADING,
+            EscrowError::InvalidOracle
+        );
+
+        Ok(Self {
+            price: u64_at(AGG_PRICE_OFFSET) as i64,
+            conf: u64_at(AGG_CONF_OFFSET),
+            expo: u32_at(EXPO_OFFSET) as i32,
+            publish_time: u64_at(TIMESTAMP_OFFSET) as i64,
+        })
+    }
+
+    // Rejects prices older than `max_staleness` seconds or whose confidence
+    // interval is wider than `max_confidence_bps` of the price.
+    pub fn validate(&self, now: i64, max_staleness: u32, max_confidence_bps: u16) -> Result<()> {
+        require!(self.price > 0, EscrowError::InvalidOracle);
+        require!(
+            now.saturating_sub(self.publish_time) <= max_staleness as i64,
+            EscrowError::StalePrice
+        );
+        require!(
+            (self.conf as u128) * 10_000 <= (self.price as u128) * max_confidence_bps as u128,
+            EscrowError::PriceUncertain
+        );
+        Ok(())
+    }
+
+    // mint_b base units for `amount` base units of mint_a at this price,
+    // adjusted by `premium_bps` and rounded up in the maker's favour.
+    pub fn quote(
+        &self,
+        amount: u64,
+        decimals_a: u8,
+        decimals_b: u8,
+        premium_bps: i16,
+    ) -> Result<u64> {
+        let scale = self.expo + decimals_b as i32 - decimals_a as i32;
+
+        let mut numerator = (amount as u128)
+            .checked_mul(self.price as u128)
+            .and_then(|n| n.checked_mul((10_000 + premium_bps as i32) as u128))
+            .ok_or(EscrowError::InvalidAmount)?;
+        let mut denominator = 10_000u128;
+
+        let factor = 10u128
+            .checked_pow(scale.unsigned_abs())
+            .ok_or(EscrowError::InvalidAmount)?;
+        if scale >= 0 {
+            numerator = numerator
+                .checked_mul(factor)
+                .ok_or(EscrowError::InvalidAmount)?;
+        } else {
+            denominator = denominator
+                .checked_mul(factor)
+                .ok_or(EscrowError::InvalidAmount)?;
+        }
+
+        u64::try_from(numerator.div_ceil(denominator))
+            .map_err(|_| EscrowError::InvalidAmount.into())
+    }
+}
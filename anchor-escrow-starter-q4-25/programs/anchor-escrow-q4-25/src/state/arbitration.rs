@@ -0,0 +1,72 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::EscrowError, BPS_DENOMINATOR};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum ArbitrationStatus {
+    // Payment is locked and the work has not been handed in yet.
+    Funded,
+    // The beneficiary marked the work delivered; the maker's review period runs.
+    Delivered,
+    // The maker disputed the work; the arbiter's ruling period runs.
+    Disputed,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ArbitratedEscrow {
+    pub seed: u64,
+    pub maker: Pubkey,
+    pub beneficiary: Pubkey,
+    pub arbiter: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub review_period: u32,
+    pub ruling_period: u32,
+    pub fallback_bps: u16,
+    pub status: ArbitrationStatus,
+    pub updated_at: i64,
+    pub bump: u8,
+}
+
+impl ArbitratedEscrow {
+    // Splits `total` into (beneficiary, maker) shares, `beneficiary_bps` to
+    // the beneficiary. The maker gets the rounding dust.
+    pub fn split(total: u64, beneficiary_bps: u16) -> Result<(u64, u64)> {
+        require!(
+            beneficiary_bps as u64 <= BPS_DENOMINATOR,
+            EscrowError::InvalidAmount
+        );
+        let to_beneficiary =
+            (total as u128 * beneficiary_bps as u128 / BPS_DENOMINATOR as u128) as u64;
+        Ok((to_beneficiary, total - to_beneficiary))
+    }
+
+    // Split that applies once the party the escrow is waiting on has been
+    // silent too long: a delivery the maker never reviewed pays out in full,
+    // a dispute the arbiter never ruled on splits by `fallback_bps`.
+    pub fn timeout_split(&self, now: i64) -> Result<u16> {
+        let (period, beneficiary_bps) = match self.status {
+            ArbitrationStatus::Funded => return err!(EscrowError::InvalidStatus),
+            ArbitrationStatus::Delivered => (self.review_period, BPS_DENOMINATOR as u16),
+            ArbitrationStatus::Disputed => (self.ruling_period, self.fallback_bps),
+        };
+        require_gte!(
+            now,
+            self.updated_at.saturating_add(period as i64),
+            EscrowError::TimeoutNotReached
+        );
+        Ok(beneficiary_bps)
+    }
+}
+
+// seed: Lets one maker open several arbitrated escrows; part of the PDA seeds.
+// maker: The wallet paying for the work; gets back whatever the beneficiary isn't awarded.
+// beneficiary: The wallet doing the work and receiving the payment.
+// arbiter: The only wallet that can rule on a dispute.
+// mint & amount: The payment parked in the escrow's vault ATA.
+// review_period: Seconds the maker has to release or dispute after delivery.
+// ruling_period: Seconds the arbiter has to rule once the work is disputed.
+// fallback_bps: Beneficiary's share of the vault if the arbiter never rules.
+// status: Where the deal stands; `updated_at` is when it got there.
+// bump: Cached bump byte for the `[b"arbitrated", maker, seed]` PDA.
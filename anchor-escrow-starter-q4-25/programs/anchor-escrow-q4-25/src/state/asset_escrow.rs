@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum CoreAssetTarget {
+    Asset(Pubkey),
+    Collection(Pubkey),
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct AssetOffer {
+    pub seed: u64,
+    pub maker: Pubkey,
+    pub asset: Pubkey,
+    pub mint_b: Pubkey,
+    pub receive: u64,
+    pub expires_at: i64,
+    pub bump: u8,
+}
+
+// AssetOffer: the maker sells a Metaplex Core asset for tokens.
+// asset: The Core asset, owned by the offer PDA until it is taken or refunded.
+// mint_b & receive: The token and amount the maker wants for the asset.
+// expires_at: Unix timestamp after which the offer can no longer be taken.
+
+#[account]
+#[derive(InitSpace)]
+pub struct AssetBid {
+    pub seed: u64,
+    pub maker: Pubkey,
+    pub mint_a: Pubkey,
+    pub deposit: u64,
+    pub target: CoreAssetTarget,
+    pub expires_at: i64,
+    pub bump: u8,
+}
+
+// AssetBid: the maker pays tokens for a Metaplex Core asset.
+// mint_a & deposit: The token and amount parked in the bid's vault.
+// target: Either one specific asset, or any asset from a collection.
+// expires_at: Unix timestamp after which the bid can no longer be filled.
@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+
+pub const MAX_BASKET_LEGS: usize = 5;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub struct BasketLeg {
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct BasketEscrow {
+    pub seed: u64,
+    pub maker: Pubkey,
+    #[max_len(MAX_BASKET_LEGS)]
+    pub offered: Vec<BasketLeg>,
+    #[max_len(MAX_BASKET_LEGS)]
+    pub requested: Vec<BasketLeg>,
+    pub expires_at: i64,
+    pub bump: u8,
+}
+
+// seed: Lets one maker open several baskets; part of the PDA seeds.
+// maker: The wallet that deposited the offered legs and receives the requested ones.
+// offered: Mints and amounts sitting in the basket's vault ATAs, one vault per mint.
+// requested: Mints and amounts the taker has to pay the maker.
+// expires_at: Unix timestamp after which the basket can no longer be taken
+//             and anyone may crank it closed back to the maker.
+// bump: Cached bump byte for the basket PDA.
+//
+// Token accounts for every leg are passed as remaining accounts, in leg order,
+// and are checked against these lists before anything moves.
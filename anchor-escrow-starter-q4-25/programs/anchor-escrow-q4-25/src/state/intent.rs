@@ -0,0 +1,54 @@
+use anchor_lang::prelude::*;
+
+use crate::Pricing;
+
+// Domain tag prefixed to every signed intent, followed by the program id,
+// so a signature can't be replayed as some other program's message.
+pub const INTENT_DOMAIN: &[u8] = b"escrow-intent";
+
+// Offer terms the maker signs off-chain. A relayer submits them with
+// `make_with_intent` alongside an ed25519 precompile instruction carrying
+// the maker's signature over `MakeIntent::message`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MakeIntent {
+    pub maker: Pubkey,
+    pub seed: u64,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub deposit: u64,
+    pub receive: u64,
+    pub expires_at: i64,
+    pub taker: Option<Pubkey>,
+    pub pricing: Pricing,
+    pub nonce: u64,
+    pub deadline: i64,
+}
+
+impl MakeIntent {
+    // The exact bytes the maker signs.
+    pub fn message(&self) -> Result<Vec<u8>> {
+        let mut message = [INTENT_DOMAIN, crate::ID.as_ref()].concat();
+        self.serialize(&mut message)?;
+        Ok(message)
+    }
+}
+
+// maker ... pricing: The same terms `make` takes, for an ask.
+// nonce: Spent by the first `make_with_intent` that uses it; see `UsedNonce`.
+// deadline: Unix timestamp after which the intent can no longer be submitted.
+
+#[account]
+#[derive(InitSpace)]
+pub struct UsedNonce {
+    pub maker: Pubkey,
+    pub nonce: u64,
+    pub payer: Pubkey,
+    pub deadline: i64,
+    pub bump: u8,
+}
+
+// Marks a maker's intent nonce as spent. Created at `[b"nonce", maker, nonce]`
+// by the relayer that submits the intent, so the same signed intent can't be
+// submitted again once its escrow has closed. Past the intent's `deadline`
+// the intent can't be submitted anyway, so `close_nonce` may close it and
+// return the rent to `payer`.
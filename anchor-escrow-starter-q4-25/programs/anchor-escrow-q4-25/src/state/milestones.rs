@@ -0,0 +1,70 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+
+pub const MAX_MILESTONES: usize = 8;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct Milestone {
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub deadline: i64,
+    pub disputed: bool,
+}
+
+// recipient & amount: Who gets paid for the milestone, and how much.
+// deadline: After this the milestone pays out without the maker's approval,
+// unless the maker disputed it in time.
+// disputed: Set by the maker to stop the automatic release; the arbiter then
+// decides how the milestone is split.
+
+#[account]
+#[derive(InitSpace)]
+pub struct MilestoneEscrow {
+    pub seed: u64,
+    pub maker: Pubkey,
+    pub mint: Pubkey,
+    pub arbiter: Pubkey,
+    pub ruling_period: u32,
+    pub fallback_bps: u16,
+    #[max_len(MAX_MILESTONES)]
+    pub milestones: Vec<Milestone>,
+    pub next: u8,
+    pub disputed_at: i64,
+    pub bump: u8,
+}
+
+impl MilestoneEscrow {
+    // The milestone up for release. Milestones are released strictly in order.
+    pub fn current(&self) -> Option<&Milestone> {
+        self.milestones.get(self.next as usize)
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.next as usize == self.milestones.len()
+    }
+
+    // A disputed milestone the arbiter has not ruled on in time splits by
+    // `fallback_bps`.
+    pub fn timeout_split(&self, now: i64) -> Result<u16> {
+        let milestone = self.current().ok_or(EscrowError::InvalidStatus)?;
+        require!(milestone.disputed, EscrowError::InvalidStatus);
+        require_gte!(
+            now,
+            self.disputed_at.saturating_add(self.ruling_period as i64),
+            EscrowError::TimeoutNotReached
+        );
+        Ok(self.fallback_bps)
+    }
+}
+
+// seed: Lets one maker open several milestone escrows; part of the PDA seeds.
+// maker: The wallet that funded the milestones and gets back what is never released.
+// mint: The payment token; the vault ATA holds the sum of all unreleased milestones.
+// arbiter: The only wallet that can rule on a disputed milestone.
+// ruling_period: Seconds the arbiter has to rule once a milestone is disputed.
+// fallback_bps: Recipient's share of a disputed milestone if the arbiter never rules.
+// milestones: The payment schedule, released in order.
+// next: Index of the first unreleased milestone.
+// disputed_at: When the current milestone was disputed; starts the ruling period.
+// bump: Cached bump byte for the `[b"milestones", maker, seed]` PDA.
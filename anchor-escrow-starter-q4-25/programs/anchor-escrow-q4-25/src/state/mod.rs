@@ -1,5 +1,52 @@
 use anchor_lang::prelude::*;
 
+use crate::{errors::EscrowError, oracle::OraclePrice};
+
+pub mod asset_escrow;
+pub use asset_escrow::*;
+
+pub mod pricing;
+pub use pricing::*;
+
+pub mod protocol;
+pub use protocol::*;
+
+pub mod basket;
+pub use basket::*;
+
+pub mod arbitration;
+pub use arbitration::*;
+
+pub mod milestones;
+pub use milestones::*;
+
+pub mod vesting;
+pub use vesting::*;
+
+pub mod stream;
+pub use stream::*;
+
+pub mod intent;
+pub use intent::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum Side {
+    // The maker sells `deposit` of mint_a for `receive` of mint_b; fills are
+    // counted in mint_a.
+    Ask,
+    // The maker pays `deposit` of mint_a for `receive` of mint_b; fills are
+    // counted in mint_b, the asset the maker is buying.
+    Bid,
+}
+
+// Token movements of a single fill.
+pub struct Fill {
+    // mint_b the taker pays.
+    pub paid: u64,
+    // mint_a the taker withdraws from the vault.
+    pub withdrawn: u64,
+}
+
 #[account]
 #[derive(InitSpace)]
 pub struct Escrow {
@@ -8,9 +55,148 @@ pub struct Escrow {
     pub mint_a: Pubkey,
     pub mint_b: Pubkey,
     pub receive: u64,
+    pub deposit: u64,
+    pub remaining: u64,
+    pub expires_at: i64,
+    pub taker: Option<Pubkey>,
+    pub pricing: Pricing,
+    pub side: Side,
+    pub version: u32,
+    pub fee_bps: u16,
+    pub referral_bps: u16,
+    pub rent_payer: Pubkey,
+    pub vault_rent_payer: Pubkey,
     pub bump: u8
 }
 
+impl Escrow {
+    // What the whole deposit costs right now, in mint_b. Fixed offers always
+    // cost `receive`; oracle and auction offers never cost less than `receive`.
+    pub fn current_receive(
+        &self,
+        oracle: Option<&AccountInfo>,
+        decimals_a: u8,
+        decimals_b: u8,
+        now: i64,
+    ) -> Result<u64> {
+        match self.pricing {
+            Pricing::Fixed => Ok(self.receive),
+            Pricing::Oracle {
+                feed,
+                premium_bps,
+                max_staleness,
+                max_confidence_bps,
+            } => {
+                let oracle = oracle.ok_or(EscrowError::InvalidOracle)?;
+                require_keys_eq!(oracle.key(), feed, EscrowError::InvalidOracle);
+
+                let price = OraclePrice::load(oracle)?;
+                price.validate(now, max_staleness, max_confidence_bps)?;
+
+                let quoted = price.quote(self.deposit, decimals_a, decimals_b, premium_bps)?;
+                Ok(quoted.max(self.receive))
+            }
+            Pricing::DutchAuction {
+                start_receive,
+                end_receive,
+                start_time,
+                duration,
+                curve,
+            } => Ok(auction_receive(
+                start_receive,
+                end_receive,
+                start_time,
+                duration,
+                curve,
+                now,
+            )
+            .max(self.receive)),
+        }
+    }
+
+    // Splits a mint_b payment into (protocol fee, referrer share of that fee).
+    // Both round down, so the maker is never charged more than `fee_bps`.
+    pub fn fee_split(&self, paid: u64, referred: bool) -> (u64, u64) {
+        let fee = (paid as u128 * self.fee_bps as u128 / BPS_DENOMINATOR as u128) as u64;
+        let referral = if referred {
+            (fee as u128 * self.referral_bps as u128 / BPS_DENOMINATOR as u128) as u64
+        } else {
+            0
+        };
+        (fee, referral)
+    }
+
+    // Fills `amount`, counted in the side's unit, at a price of `receive`
+    // for the whole deposit.
+    pub fn fill_for(&self, amount: u64, receive: u64) -> Result<Fill> {
+        match self.side {
+            Side::Ask => Ok(Fill {
+                paid: self.receive_for(amount, receive)?,
+                withdrawn: amount,
+            }),
+            Side::Bid => Ok(Fill {
+                paid: amount,
+                withdrawn: self.payout_for(amount)?,
+            }),
+        }
+    }
+
+    // Amount of mint_a a bid pays out for `amount` of mint_b. Rounds down in
+    // the maker's favour on the cumulative fill, so the last fill empties the vault.
+    pub fn payout_for(&self, amount: u64) -> Result<u64> {
+        let filled_before = self.receive - self.remaining;
+        let filled_after = filled_before
+            .checked_add(amount)
+            .ok_or(EscrowError::InvalidAmount)?;
+
+        let owed = |filled: u64| -> Result<u128> {
+            Ok((filled as u128)
+                .checked_mul(self.deposit as u128)
+                .ok_or(EscrowError::InvalidAmount)?
+                / self.receive as u128)
+        };
+
+        u64::try_from(owed(filled_after)? - owed(filled_before)?)
+            .map_err(|_| EscrowError::InvalidAmount.into())
+    }
+
+    // Largest ask fill, in mint_a, whose cost at `receive` fits in `budget`
+    // of mint_b.
+    pub fn max_fill(&self, budget: u64, receive: u64) -> u64 {
+        let filled = (self.deposit - self.remaining) as u128;
+        let owed = (filled * receive as u128).div_ceil(self.deposit as u128);
+
+        // Only overflows when the budget covers far more than the whole offer.
+        (owed + budget as u128)
+            .checked_mul(self.deposit as u128)
+            .map_or(self.remaining, |affordable| {
+                let affordable = affordable / receive as u128 - filled;
+                affordable.min(self.remaining as u128) as u64
+            })
+    }
+
+    // Amount of mint_b owed for filling `amount` of mint_a when the whole
+    // deposit costs `receive`. Computed on the cumulative fill so rounding
+    // (always up, in the maker's favour) never drifts and the final fill of
+    // a fixed offer settles the exact `receive` total.
+    pub fn receive_for(&self, amount: u64, receive: u64) -> Result<u64> {
+        let filled_before = self.deposit - self.remaining;
+        let filled_after = filled_before
+            .checked_add(amount)
+            .ok_or(EscrowError::InvalidAmount)?;
+
+        let owed = |filled: u64| -> Result<u128> {
+            Ok((filled as u128)
+                .checked_mul(receive as u128)
+                .ok_or(EscrowError::InvalidAmount)?
+                .div_ceil(self.deposit as u128))
+        };
+
+        u64::try_from(owed(filled_after)? - owed(filled_before)?)
+            .map_err(|_| EscrowError::InvalidAmount.into())
+    }
+}
+
 // seed: Random number used during seed derivation so one maker
 //       can open multiple escrows with the same token pair;
 //       stored on-chain so we can always re-derive the PDA.
@@ -18,8 +204,23 @@ pub struct Escrow {
 //        needed for refunds and to receive payment.
 // mint_a & mint_b: The SPL mints addresses for the "give" and "get"
 //                  sides of the swap.
-// receive: How much of token B the maker wants.
-//          (The vault's balance itself shows how much token A
-//           was deposited, so we don't store that.)
+// receive: How much of token B the maker wants for the whole deposit.
+// deposit: How much of token A the maker originally deposited;
+//          fills are priced pro-rata against deposit/receive.
+// remaining: How much of the offer is still unfilled: token A for asks,
+//            token B for bids.
+//            The escrow and vault close once this reaches zero.
+// expires_at: Unix timestamp after which the offer can no longer be taken
+//             and anyone may crank it closed back to the maker.
+// taker: Optional counterparty the offer is locked to. When set, only
+//        that wallet can take; when None, the offer is public.
+// pricing: How the taker's payment is computed; see `Pricing`.
+// side: Whether the maker is selling token A (ask) or buying token B (bid).
+// version: Bumped on every `amend` so takers can refuse terms they have not seen.
+// fee_bps & referral_bps: Protocol fee settings when the offer was made. Takes
+//                         charge these, so later `update_protocol` calls never
+//                         change what the maker nets.
+// rent_payer & vault_rent_payer: Who funded the escrow and the vault accounts;
+//                                their rent goes back to them when the offer closes.
 // bump: Cached bump byte; deriving it on the fly costs compute,
 //       so we save it once.
\ No newline at end of file
@@ -0,0 +1,119 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+
+// Fixed-point one for the exponential auction curve. 2^60 leaves room to
+// square-root `ratio * ONE` inside a u128.
+const ONE: u128 = 1 << 60;
+// Binary digits of elapsed / duration used by the exponential curve.
+const CURVE_BITS: u32 = 32;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum AuctionCurve {
+    // The price falls by the same amount every second.
+    Linear,
+    // The price falls by the same fraction every second.
+    Exponential,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum Pricing {
+    // The taker pays the escrow's `receive` for the whole deposit.
+    Fixed,
+    // The taker pays the oracle price of the deposit, in mint_b, adjusted by
+    // `premium_bps` (negative for a discount). `receive` becomes the floor.
+    Oracle {
+        feed: Pubkey,
+        premium_bps: i16,
+        max_staleness: u32,
+        max_confidence_bps: u16,
+    },
+    // The price of the whole deposit falls from `start_receive` at
+    // `start_time` to `end_receive` after `duration` seconds, then stays
+    // there. `receive` becomes the floor.
+    DutchAuction {
+        start_receive: u64,
+        end_receive: u64,
+        start_time: i64,
+        duration: u32,
+        curve: AuctionCurve,
+    },
+}
+
+impl Pricing {
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            Pricing::Fixed => {}
+            Pricing::Oracle {
+                premium_bps,
+                max_confidence_bps,
+                ..
+            } => {
+                require!(*premium_bps > -10_000, EscrowError::InvalidPricing);
+                require!(
+                    *max_confidence_bps > 0 && *max_confidence_bps <= 10_000,
+                    EscrowError::InvalidPricing
+                );
+            }
+            Pricing::DutchAuction {
+                start_receive,
+                end_receive,
+                duration,
+                ..
+            } => {
+                require!(
+                    *end_receive > 0 && start_receive >= end_receive,
+                    EscrowError::InvalidPricing
+                );
+                require_gt!(*duration, 0, EscrowError::InvalidPricing);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn is_fixed(&self) -> bool {
+        matches!(self, Pricing::Fixed)
+    }
+}
+
+// Price of the whole deposit at `now`, rounded up in the maker's favour.
+pub fn auction_receive(
+    start_receive: u64,
+    end_receive: u64,
+    start_time: i64,
+    duration: u32,
+    curve: AuctionCurve,
+    now: i64,
+) -> u64 {
+    let elapsed = now.saturating_sub(start_time).max(0) as u128;
+    let duration = duration as u128;
+    if elapsed == 0 {
+        return start_receive;
+    }
+    if elapsed >= duration {
+        return end_receive;
+    }
+
+    match curve {
+        AuctionCurve::Linear => {
+            let drop = (start_receive - end_receive) as u128 * elapsed / duration;
+            start_receive - drop as u64
+        }
+        // start * (end / start)^(elapsed / duration), built from the binary
+        // digits of the exponent: the i-th digit multiplies in the 2^i-th
+        // root of the ratio, each root being the square root of the previous.
+        AuctionCurve::Exponential => {
+            let exponent = (elapsed << CURVE_BITS) / duration;
+            let mut root = end_receive as u128 * ONE / start_receive as u128;
+            let mut factor = ONE;
+            for bit in (0..CURVE_BITS).rev() {
+                root = (root * ONE).isqrt();
+                if exponent & (1 << bit) != 0 {
+                    factor = factor * root / ONE;
+                }
+            }
+            let receive = (start_receive as u128 * factor).div_ceil(ONE) as u64;
+            receive.clamp(end_receive, start_receive)
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::EscrowError;
+
+pub const BPS_DENOMINATOR: u64 = 10_000;
+pub const MAX_FEE_BPS: u16 = 1_000;
+
+#[account]
+#[derive(InitSpace)]
+pub struct ProtocolConfig {
+    pub authority: Pubkey,
+    pub treasury: Pubkey,
+    pub fee_bps: u16,
+    pub referral_bps: u16,
+    pub bump: u8,
+}
+
+impl ProtocolConfig {
+    // Fees are opt-in for the deployment: until the protocol PDA is
+    // initialized, takes settle without one.
+    pub fn load(account: &AccountInfo) -> Result<Option<Self>> {
+        if account.data_is_empty() {
+            return Ok(None);
+        }
+        require_keys_eq!(
+            *account.owner,
+            crate::ID,
+            ErrorCode::AccountOwnedByWrongProgram
+        );
+
+        Self::try_deserialize(&mut &account.try_borrow_data()?[..]).map(Some)
+    }
+
+    // Fee settings a new escrow is made with, as (fee_bps, referral_bps).
+    pub fn fee_terms(account: &AccountInfo) -> Result<(u16, u16)> {
+        Ok(Self::load(account)?.map_or((0, 0), |config| (config.fee_bps, config.referral_bps)))
+    }
+
+    pub fn validate(fee_bps: u16, referral_bps: u16) -> Result<()> {
+        require!(fee_bps <= MAX_FEE_BPS, EscrowError::InvalidFee);
+        require!(
+            referral_bps as u64 <= BPS_DENOMINATOR,
+            EscrowError::InvalidFee
+        );
+        Ok(())
+    }
+}
+
+// authority: Wallet allowed to change the fee settings.
+// treasury: Owner of the token accounts that collect protocol fees.
+// fee_bps: Fee new escrows are made with, taken out of the maker's mint_b proceeds, capped at MAX_FEE_BPS.
+// referral_bps: Share of that fee forwarded to the referrer, when the taker names one.
+// bump: Cached bump byte for the `[b"protocol"]` PDA.
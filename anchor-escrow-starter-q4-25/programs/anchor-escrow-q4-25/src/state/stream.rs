@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+
+#[account]
+#[derive(InitSpace)]
+pub struct PaymentStream {
+    pub seed: u64,
+    pub sender: Pubkey,
+    pub recipient: Pubkey,
+    pub mint: Pubkey,
+    pub rate: u64,
+    pub start: i64,
+    pub stop: i64,
+    pub deposited: u64,
+    pub withdrawn: u64,
+    pub bump: u8,
+}
+
+impl PaymentStream {
+    // Tokens earned by the recipient at `now`: `rate` per second between
+    // `start` and `stop`, but never more than the sender has deposited.
+    pub fn accrued(&self, now: i64) -> u64 {
+        let elapsed = now.min(self.stop).saturating_sub(self.start).max(0) as u128;
+        (elapsed * self.rate as u128).min(self.deposited as u128) as u64
+    }
+
+    pub fn withdrawable(&self, now: i64) -> u64 {
+        self.accrued(now) - self.withdrawn
+    }
+}
+
+// seed: Lets one sender open several streams; part of the PDA seeds.
+// sender: The wallet funding the stream; gets back the unearned balance and the rent.
+// recipient: The wallet the tokens stream to.
+// mint: The streamed token, held in the stream's vault ATA.
+// rate: Tokens earned per second between `start` and `stop`.
+// deposited: Everything the sender has put in. It only caps what has accrued, so
+// a dry stream keeps earning at `rate` and a top-up pays out the dry period too.
+// withdrawn: Tokens the recipient has already taken out.
+// bump: Cached bump byte for the `[b"stream", sender, seed]` PDA.
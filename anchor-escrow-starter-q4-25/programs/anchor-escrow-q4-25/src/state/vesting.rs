@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+
+#[account]
+#[derive(InitSpace)]
+pub struct VestingSchedule {
+    pub seed: u64,
+    pub grantor: Pubkey,
+    pub beneficiary: Pubkey,
+    pub mint: Pubkey,
+    pub total: u64,
+    pub claimed: u64,
+    pub start: i64,
+    pub cliff: u32,
+    pub duration: u32,
+    pub period: u32,
+    pub revocable: bool,
+    pub revoked: bool,
+    pub bump: u8,
+}
+
+impl VestingSchedule {
+    // Tokens vested at `now`: nothing before the cliff, then `total` unlocked
+    // linearly over `duration` in steps of `period` seconds. A revoked grant
+    // is frozen at whatever had vested when it was revoked.
+    pub fn vested(&self, now: i64) -> u64 {
+        if self.revoked {
+            return self.total;
+        }
+
+        let elapsed = now.saturating_sub(self.start);
+        if elapsed < self.cliff as i64 {
+            return 0;
+        }
+        if elapsed >= self.duration as i64 {
+            return self.total;
+        }
+
+        let unlocked = elapsed as u64 / self.period as u64 * self.period as u64;
+        (self.total as u128 * unlocked as u128 / self.duration as u128) as u64
+    }
+
+    pub fn claimable(&self, now: i64) -> u64 {
+        self.vested(now) - self.claimed
+    }
+
+    pub fn is_settled(&self, now: i64) -> bool {
+        self.claimed == self.vested(now) && (self.revoked || self.claimed == self.total)
+    }
+}
+
+// seed: Lets one grantor open several schedules; part of the PDA seeds.
+// grantor: The wallet that funded the grant, gets the rent back and can revoke it if `revocable`.
+// beneficiary: The only wallet that can claim vested tokens.
+// mint & total: The granted token and amount, held in the schedule's vault ATA.
+// claimed: Tokens the beneficiary has already withdrawn.
+// start: When vesting begins. `cliff`, `duration` and `period` are seconds counted from it.
+// revocable & revoked: Whether the grantor may claw back unvested tokens, and whether they did.
+// bump: Cached bump byte for the `[b"vesting", grantor, seed]` PDA.
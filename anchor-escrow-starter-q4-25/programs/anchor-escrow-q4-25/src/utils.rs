@@ -0,0 +1,64 @@
+use anchor_lang::prelude::*;
+
+// Helpers for token accounts that arrive through `remaining_accounts`,
+// where Anchor's `associated_token::*` constraints can't be used.
+
+use crate::errors::EscrowError;
+use anchor_spl::{
+    associated_token::{create_idempotent, get_associated_token_address_with_program_id, Create},
+    token_interface::Mint,
+};
+
+// Checks that `account` is the ATA of `owner` for `mint` under `token_program`.
+pub fn require_ata(
+    account: &AccountInfo,
+    owner: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> Result<()> {
+    require_keys_eq!(
+        account.key(),
+        get_associated_token_address_with_program_id(owner, mint, token_program),
+        EscrowError::InvalidRemainingAccounts
+    );
+    Ok(())
+}
+
+// Checks `account` is the expected mint owned by `token_program` and returns its decimals.
+pub fn load_mint_decimals<'info>(
+    account: &'info AccountInfo<'info>,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> Result<u8> {
+    require_keys_eq!(account.key(), *mint, EscrowError::InvalidRemainingAccounts);
+    require_keys_eq!(
+        *account.owner,
+        *token_program,
+        EscrowError::InvalidRemainingAccounts
+    );
+
+    let mint_account = InterfaceAccount::<Mint>::try_from(account)?;
+    Ok(mint_account.decimals)
+}
+
+// Creates the ATA if it doesn't exist yet, the remaining-accounts equivalent of `init_if_needed`.
+pub fn create_ata_if_needed<'info>(
+    payer: AccountInfo<'info>,
+    associated_token: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    mint: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    associated_token_program: AccountInfo<'info>,
+) -> Result<()> {
+    let create_accounts = Create {
+        payer,
+        associated_token,
+        authority,
+        mint,
+        system_program,
+        token_program,
+    };
+
+    create_idempotent(CpiContext::new(associated_token_program, create_accounts))
+}
@@ -2,7 +2,7 @@ import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { AnchorEscrowQ425 } from "../target/types/anchor_escrow_q4_25";
 import { expect } from "chai";
-import { getAssociatedTokenAddressSync, createAssociatedTokenAccountInstruction, createMint, mintTo, TOKEN_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { getAssociatedTokenAddressSync, createAssociatedTokenAccountInstruction, createMint, mintTo, approve, TOKEN_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID } from "@solana/spl-token";
 
 describe("anchor_escrow_q4_25", () => {
   const provider = anchor.AnchorProvider.env();
@@ -25,8 +25,14 @@ describe("anchor_escrow_q4_25", () => {
   let escrowBump: number;
   let vault: anchor.web3.PublicKey;
 
+  const [protocolPda] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("protocol")],
+    program.programId
+  );
+
   const depositAmount = 100;
   const receiveAmount = 200;
+  const expiresAt = new anchor.BN(Math.floor(Date.now() / 1000) + 3600);
 
   before(async () => {
     // Airdrop SOL to maker and taker
@@ -65,14 +71,16 @@ describe("anchor_escrow_q4_25", () => {
 
     // Make
     await program.methods
-      .make(seed1, new anchor.BN(depositAmount), new anchor.BN(receiveAmount))
+      .make(seed1, new anchor.BN(depositAmount), new anchor.BN(receiveAmount), expiresAt, null, { fixed: {} })
       .accountsStrict({
         maker: maker,
+        payer: maker,
         mintA: mintA,
         mintB: mintB,
         makerAtaA: makerAtaA,
         escrow: escrowPda,
         vault: vault,
+        protocol: protocolPda,
         associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
@@ -84,6 +92,8 @@ describe("anchor_escrow_q4_25", () => {
     expect(escrowAccount.mintA.toBase58()).to.equal(mintA.toBase58());
     expect(escrowAccount.mintB.toBase58()).to.equal(mintB.toBase58());
     expect(escrowAccount.receive.toNumber()).to.equal(receiveAmount);
+    expect(escrowAccount.deposit.toNumber()).to.equal(depositAmount);
+    expect(escrowAccount.remaining.toNumber()).to.equal(depositAmount);
     expect(escrowAccount.bump).to.equal(escrowBump);
 
     const vaultBalance = (await provider.connection.getTokenAccountBalance(vault)).value.uiAmount;
@@ -98,6 +108,8 @@ describe("anchor_escrow_q4_25", () => {
         makerAtaA: makerAtaA,
         escrow: escrowPda,
         vault: vault,
+        rentPayer: maker,
+        vaultRentPayer: maker,
         associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
@@ -122,14 +134,16 @@ describe("anchor_escrow_q4_25", () => {
 
     // Make (again for take path)
     await program.methods
-      .make(seed2, new anchor.BN(depositAmount), new anchor.BN(receiveAmount))
+      .make(seed2, new anchor.BN(depositAmount), new anchor.BN(receiveAmount), expiresAt, null, { fixed: {} })
       .accountsStrict({
         maker: maker,
+        payer: maker,
         mintA: mintA,
         mintB: mintB,
         makerAtaA: makerAtaA,
         escrow: escrowPda,
         vault: vault,
+        protocol: protocolPda,
         associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
@@ -142,7 +156,7 @@ describe("anchor_escrow_q4_25", () => {
 
     // Take
     await program.methods
-      .take()
+      .take(new anchor.BN(depositAmount), new anchor.BN(receiveAmount), new anchor.BN(depositAmount), null)
       .accountsStrict({
         taker: taker.publicKey,
         maker: maker,
@@ -153,6 +167,12 @@ describe("anchor_escrow_q4_25", () => {
         makerAtaB: makerAtaB,
         escrow: escrowPda,
         vault: vault,
+        rentPayer: maker,
+        vaultRentPayer: maker,
+        protocol: protocolPda,
+        treasuryAtaB: null,
+        referrerAtaB: null,
+        oracle: null,
         associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
@@ -174,4 +194,2611 @@ describe("anchor_escrow_q4_25", () => {
     const makerBalanceB = (await provider.connection.getTokenAccountBalance(makerAtaB)).value.uiAmount;
     expect(makerBalanceB).to.equal(receiveAmount);
   });
-});
\ No newline at end of file
+  it("Partially fills the escrow before closing it", async () => {
+    const seed3 = new anchor.BN(3333);
+    [escrowPda, escrowBump] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), maker.toBuffer(), seed3.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    vault = getAssociatedTokenAddressSync(mintA, escrowPda, true);
+
+    await program.methods
+      .make(seed3, new anchor.BN(depositAmount), new anchor.BN(receiveAmount), expiresAt, null, { fixed: {} })
+      .accountsStrict({
+        maker: maker,
+        payer: maker,
+        mintA: mintA,
+        mintB: mintB,
+        makerAtaA: makerAtaA,
+        escrow: escrowPda,
+        vault: vault,
+        protocol: protocolPda,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    const takeAccounts = {
+      taker: taker.publicKey,
+      maker: maker,
+      mintA: mintA,
+      mintB: mintB,
+      takerAtaA: takerAtaA,
+      takerAtaB: takerAtaB,
+      makerAtaB: makerAtaB,
+      escrow: escrowPda,
+      vault: vault,
+      rentPayer: maker,
+      vaultRentPayer: maker,
+      protocol: protocolPda,
+      treasuryAtaB: null,
+      referrerAtaB: null,
+      oracle: null,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    };
+
+    const takerBalanceABefore = (await provider.connection.getTokenAccountBalance(takerAtaA)).value.uiAmount;
+    const makerBalanceBBefore = (await provider.connection.getTokenAccountBalance(makerAtaB)).value.uiAmount;
+
+    // Fill a quarter of the offer
+    await program.methods
+      .take(new anchor.BN(depositAmount / 4), new anchor.BN(receiveAmount), new anchor.BN(depositAmount), null)
+      .accountsStrict(takeAccounts)
+      .signers([taker])
+      .rpc();
+
+    const escrowAccount = await program.account.escrow.fetch(escrowPda);
+    expect(escrowAccount.remaining.toNumber()).to.equal(depositAmount - depositAmount / 4);
+
+    const vaultBalance = (await provider.connection.getTokenAccountBalance(vault)).value.uiAmount;
+    expect(vaultBalance).to.equal(depositAmount - depositAmount / 4);
+
+    // Fill the rest
+    await program.methods
+      .take(
+        new anchor.BN(depositAmount - depositAmount / 4),
+        new anchor.BN(receiveAmount),
+        new anchor.BN(depositAmount - depositAmount / 4),
+        null
+      )
+      .accountsStrict(takeAccounts)
+      .signers([taker])
+      .rpc();
+
+    const escrowInfo = await provider.connection.getAccountInfo(escrowPda);
+    expect(escrowInfo).to.be.null;
+
+    const vaultInfo = await provider.connection.getAccountInfo(vault);
+    expect(vaultInfo).to.be.null;
+
+    const takerBalanceA = (await provider.connection.getTokenAccountBalance(takerAtaA)).value.uiAmount;
+    expect(takerBalanceA - takerBalanceABefore).to.equal(depositAmount);
+
+    const makerBalanceB = (await provider.connection.getTokenAccountBalance(makerAtaB)).value.uiAmount;
+    expect(makerBalanceB - makerBalanceBBefore).to.equal(receiveAmount);
+  });
+  it("Hands tokens donated to the vault to the last taker", async () => {
+    const seedDust = new anchor.BN(3334);
+    [escrowPda, escrowBump] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), maker.toBuffer(), seedDust.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    vault = getAssociatedTokenAddressSync(mintA, escrowPda, true);
+
+    await mintTo(provider.connection, provider.wallet.payer, mintA, makerAtaA, provider.wallet.payer, depositAmount);
+    await mintTo(provider.connection, taker, mintB, takerAtaB, taker, receiveAmount);
+
+    await program.methods
+      .make(seedDust, new anchor.BN(depositAmount), new anchor.BN(receiveAmount), expiresAt, null, { fixed: {} })
+      .accountsStrict({
+        maker: maker,
+        payer: maker,
+        mintA: mintA,
+        mintB: mintB,
+        makerAtaA: makerAtaA,
+        escrow: escrowPda,
+        vault: vault,
+        protocol: protocolPda,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    // Anyone can send tokens straight to the vault
+    await mintTo(provider.connection, provider.wallet.payer, mintA, vault, provider.wallet.payer, 1);
+
+    const takerBalanceABefore = (await provider.connection.getTokenAccountBalance(takerAtaA)).value.uiAmount;
+
+    await program.methods
+      .take(new anchor.BN(depositAmount), new anchor.BN(receiveAmount), new anchor.BN(depositAmount + 1), null)
+      .accountsStrict({
+        taker: taker.publicKey,
+        maker: maker,
+        mintA: mintA,
+        mintB: mintB,
+        takerAtaA: takerAtaA,
+        takerAtaB: takerAtaB,
+        makerAtaB: makerAtaB,
+        escrow: escrowPda,
+        vault: vault,
+        rentPayer: maker,
+        vaultRentPayer: maker,
+        protocol: protocolPda,
+        treasuryAtaB: null,
+        referrerAtaB: null,
+        oracle: null,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([taker])
+      .rpc();
+
+    const vaultInfo = await provider.connection.getAccountInfo(vault);
+    expect(vaultInfo).to.be.null;
+
+    const takerBalanceA = (await provider.connection.getTokenAccountBalance(takerAtaA)).value.uiAmount;
+    expect(takerBalanceA - takerBalanceABefore).to.equal(depositAmount + 1);
+  });
+
+  it("Rejects takes after expiry and lets anyone crank the offer closed", async () => {
+    const seed4 = new anchor.BN(4444);
+    [escrowPda, escrowBump] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), maker.toBuffer(), seed4.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    vault = getAssociatedTokenAddressSync(mintA, escrowPda, true);
+
+    const shortExpiry = new anchor.BN(Math.floor(Date.now() / 1000) + 2);
+    await program.methods
+      .make(seed4, new anchor.BN(depositAmount), new anchor.BN(receiveAmount), shortExpiry, null, { fixed: {} })
+      .accountsStrict({
+        maker: maker,
+        payer: maker,
+        mintA: mintA,
+        mintB: mintB,
+        makerAtaA: makerAtaA,
+        escrow: escrowPda,
+        vault: vault,
+        protocol: protocolPda,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    await new Promise(resolve => setTimeout(resolve, 4000));
+
+    try {
+      await program.methods
+        .take(new anchor.BN(depositAmount), new anchor.BN(receiveAmount), new anchor.BN(depositAmount), null)
+        .accountsStrict({
+          taker: taker.publicKey,
+          maker: maker,
+          mintA: mintA,
+          mintB: mintB,
+          takerAtaA: takerAtaA,
+          takerAtaB: takerAtaB,
+          makerAtaB: makerAtaB,
+          escrow: escrowPda,
+          vault: vault,
+          rentPayer: maker,
+          vaultRentPayer: maker,
+          protocol: protocolPda,
+          treasuryAtaB: null,
+          referrerAtaB: null,
+          oracle: null,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([taker])
+        .rpc();
+      expect.fail("take should have failed");
+    } catch (err) {
+      expect((err as anchor.AnchorError).error.errorCode.code).to.equal("OfferExpired");
+    }
+
+    const makerBalanceABefore = (await provider.connection.getTokenAccountBalance(makerAtaA)).value.uiAmount;
+
+    // The taker cranks it, not the maker
+    await program.methods
+      .crankExpired()
+      .accountsStrict({
+        cranker: taker.publicKey,
+        maker: maker,
+        mintA: mintA,
+        makerAtaA: makerAtaA,
+        escrow: escrowPda,
+        vault: vault,
+        rentPayer: maker,
+        vaultRentPayer: maker,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([taker])
+      .rpc();
+
+    const escrowInfo = await provider.connection.getAccountInfo(escrowPda);
+    expect(escrowInfo).to.be.null;
+
+    const vaultInfo = await provider.connection.getAccountInfo(vault);
+    expect(vaultInfo).to.be.null;
+
+    const makerBalanceA = (await provider.connection.getTokenAccountBalance(makerAtaA)).value.uiAmount;
+    expect(makerBalanceA - makerBalanceABefore).to.equal(depositAmount);
+  });
+  it("Only lets the designated taker take a private escrow", async () => {
+    const seed5 = new anchor.BN(5555);
+    [escrowPda, escrowBump] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), maker.toBuffer(), seed5.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    vault = getAssociatedTokenAddressSync(mintA, escrowPda, true);
+
+    const counterparty = anchor.web3.Keypair.generate();
+    await program.methods
+      .make(seed5, new anchor.BN(depositAmount), new anchor.BN(receiveAmount), expiresAt, counterparty.publicKey, { fixed: {} })
+      .accountsStrict({
+        maker: maker,
+        payer: maker,
+        mintA: mintA,
+        mintB: mintB,
+        makerAtaA: makerAtaA,
+        escrow: escrowPda,
+        vault: vault,
+        protocol: protocolPda,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    const escrowAccount = await program.account.escrow.fetch(escrowPda);
+    expect(escrowAccount.taker.toBase58()).to.equal(counterparty.publicKey.toBase58());
+
+    try {
+      await program.methods
+        .take(new anchor.BN(depositAmount), new anchor.BN(receiveAmount), new anchor.BN(depositAmount), null)
+        .accountsStrict({
+          taker: taker.publicKey,
+          maker: maker,
+          mintA: mintA,
+          mintB: mintB,
+          takerAtaA: takerAtaA,
+          takerAtaB: takerAtaB,
+          makerAtaB: makerAtaB,
+          escrow: escrowPda,
+          vault: vault,
+          rentPayer: maker,
+          vaultRentPayer: maker,
+          protocol: protocolPda,
+          treasuryAtaB: null,
+          referrerAtaB: null,
+          oracle: null,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([taker])
+        .rpc();
+      expect.fail("take should have failed");
+    } catch (err) {
+      expect((err as anchor.AnchorError).error.errorCode.code).to.equal("InvalidTaker");
+    }
+
+    await program.methods
+      .refund()
+      .accountsStrict({
+        maker: maker,
+        mintA: mintA,
+        makerAtaA: makerAtaA,
+        escrow: escrowPda,
+        vault: vault,
+        rentPayer: maker,
+        vaultRentPayer: maker,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+  });
+  it("Amends an open escrow and bumps its version", async () => {
+    const seed8 = new anchor.BN(8888);
+    [escrowPda, escrowBump] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), maker.toBuffer(), seed8.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    vault = getAssociatedTokenAddressSync(mintA, escrowPda, true);
+
+    await mintTo(provider.connection, provider.wallet.payer, mintA, makerAtaA, provider.wallet.payer, depositAmount);
+    await mintTo(provider.connection, taker, mintB, takerAtaB, taker, receiveAmount);
+
+    await program.methods
+      .make(seed8, new anchor.BN(depositAmount), new anchor.BN(receiveAmount), expiresAt, null, { fixed: {} })
+      .accountsStrict({
+        maker: maker,
+        payer: maker,
+        mintA: mintA,
+        mintB: mintB,
+        makerAtaA: makerAtaA,
+        escrow: escrowPda,
+        vault: vault,
+        protocol: protocolPda,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    const amendedDeposit = 60;
+    const amendedReceive = 150;
+    await program.methods
+      .amend(new anchor.BN(amendedDeposit), new anchor.BN(amendedReceive))
+      .accountsStrict({
+        maker: maker,
+        mintA: mintA,
+        makerAtaA: makerAtaA,
+        escrow: escrowPda,
+        vault: vault,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    const escrowAccount = await program.account.escrow.fetch(escrowPda);
+    expect(escrowAccount.version).to.equal(1);
+    expect(escrowAccount.receive.toNumber()).to.equal(amendedReceive);
+    expect(escrowAccount.remaining.toNumber()).to.equal(amendedDeposit);
+
+    const vaultBalance = (await provider.connection.getTokenAccountBalance(vault)).value.uiAmount;
+    expect(vaultBalance).to.equal(amendedDeposit);
+
+    const takeAccounts = {
+      taker: taker.publicKey,
+      maker: maker,
+      mintA: mintA,
+      mintB: mintB,
+      takerAtaA: takerAtaA,
+      takerAtaB: takerAtaB,
+      makerAtaB: makerAtaB,
+      escrow: escrowPda,
+      vault: vault,
+      rentPayer: maker,
+      vaultRentPayer: maker,
+      protocol: protocolPda,
+      treasuryAtaB: null,
+      referrerAtaB: null,
+      oracle: null,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    };
+
+    // A taker pinned to the original version is rejected
+    try {
+      await program.methods
+        .take(new anchor.BN(amendedDeposit), new anchor.BN(amendedReceive), new anchor.BN(amendedDeposit), 0)
+        .accountsStrict(takeAccounts)
+        .signers([taker])
+        .rpc();
+      expect.fail("take should have failed");
+    } catch (err) {
+      expect((err as anchor.AnchorError).error.errorCode.code).to.equal("TermsChanged");
+    }
+
+    await program.methods
+      .take(new anchor.BN(amendedDeposit), new anchor.BN(amendedReceive), new anchor.BN(amendedDeposit), 1)
+      .accountsStrict(takeAccounts)
+      .signers([taker])
+      .rpc();
+
+    const escrowInfo = await provider.connection.getAccountInfo(escrowPda);
+    expect(escrowInfo).to.be.null;
+  });
+  it("Makes and takes a basket escrow", async () => {
+    const basketSeed = new anchor.BN(8888);
+    const [basket] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("basket"), maker.toBuffer(), basketSeed.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+
+    // Second offered mint
+    const mintD = await createMint(provider.connection, provider.wallet.payer, maker, null, 0);
+    const makerAtaD = getAssociatedTokenAddressSync(mintD, maker);
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(createAssociatedTokenAccountInstruction(maker, makerAtaD, maker, mintD))
+    );
+    await mintTo(provider.connection, provider.wallet.payer, mintD, makerAtaD, provider.wallet.payer, 50);
+
+    const offered = [
+      { mint: mintA, amount: new anchor.BN(10) },
+      { mint: mintD, amount: new anchor.BN(50) },
+    ];
+    const requested = [{ mint: mintB, amount: new anchor.BN(30) }];
+
+    const meta = (pubkey: anchor.web3.PublicKey, isWritable: boolean) => ({ pubkey, isWritable, isSigner: false });
+
+    await program.methods
+      .makeBasket(basketSeed, offered, requested, expiresAt)
+      .accountsStrict({
+        maker: maker,
+        basket,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .remainingAccounts(
+        offered.flatMap(({ mint }) => [
+          meta(mint, false),
+          meta(getAssociatedTokenAddressSync(mint, maker), true),
+          meta(getAssociatedTokenAddressSync(mint, basket, true), true),
+        ])
+      )
+      .rpc();
+
+    const basketAccount = await program.account.basketEscrow.fetch(basket);
+    expect(basketAccount.offered.length).to.equal(2);
+    expect(basketAccount.requested[0].mint.toBase58()).to.equal(mintB.toBase58());
+
+    const makerBalanceBBefore = (await provider.connection.getTokenAccountBalance(makerAtaB)).value.uiAmount;
+
+    const takeBasketAccounts = {
+      taker: taker.publicKey,
+      maker: maker,
+      basket,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    };
+    const takeBasketRemaining = [
+      ...offered.flatMap(({ mint }) => [
+        meta(mint, false),
+        meta(getAssociatedTokenAddressSync(mint, basket, true), true),
+        meta(getAssociatedTokenAddressSync(mint, taker.publicKey), true),
+      ]),
+      ...requested.flatMap(({ mint }) => [
+        meta(mint, false),
+        meta(getAssociatedTokenAddressSync(mint, taker.publicKey), true),
+        meta(getAssociatedTokenAddressSync(mint, maker), true),
+      ]),
+    ];
+
+    // A taker who saw a cheaper basket is rejected
+    try {
+      await program.methods
+        .takeBasket(offered, [{ mint: mintB, amount: new anchor.BN(20) }])
+        .accountsStrict(takeBasketAccounts)
+        .remainingAccounts(takeBasketRemaining)
+        .signers([taker])
+        .rpc();
+      expect.fail("take should have failed");
+    } catch (err) {
+      expect((err as anchor.AnchorError).error.errorCode.code).to.equal("TermsChanged");
+    }
+
+    await program.methods
+      .takeBasket(offered, requested)
+      .accountsStrict(takeBasketAccounts)
+      .remainingAccounts(takeBasketRemaining)
+      .signers([taker])
+      .rpc();
+
+    const basketInfo = await provider.connection.getAccountInfo(basket);
+    expect(basketInfo).to.be.null;
+
+    const takerBalanceD = (
+      await provider.connection.getTokenAccountBalance(getAssociatedTokenAddressSync(mintD, taker.publicKey))
+    ).value.uiAmount;
+    expect(takerBalanceD).to.equal(50);
+
+    const makerBalanceB = (await provider.connection.getTokenAccountBalance(makerAtaB)).value.uiAmount;
+    expect(makerBalanceB - makerBalanceBBefore).to.equal(30);
+  });
+
+  it("Lets anyone crank an expired basket back to the maker", async () => {
+    const basketSeed = new anchor.BN(8889);
+    const [basket] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("basket"), maker.toBuffer(), basketSeed.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const basketVault = getAssociatedTokenAddressSync(mintA, basket, true);
+
+    await mintTo(provider.connection, provider.wallet.payer, mintA, makerAtaA, provider.wallet.payer, 10);
+    const offered = [{ mint: mintA, amount: new anchor.BN(10) }];
+    const requested = [{ mint: mintB, amount: new anchor.BN(30) }];
+
+    const meta = (pubkey: anchor.web3.PublicKey, isWritable: boolean) => ({ pubkey, isWritable, isSigner: false });
+
+    await program.methods
+      .makeBasket(basketSeed, offered, requested, new anchor.BN(Math.floor(Date.now() / 1000) + 3))
+      .accountsStrict({
+        maker: maker,
+        basket,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .remainingAccounts([meta(mintA, false), meta(makerAtaA, true), meta(basketVault, true)])
+      .rpc();
+
+    const crank = () =>
+      program.methods
+        .crankExpiredBasket()
+        .accountsStrict({
+          cranker: taker.publicKey,
+          maker: maker,
+          basket,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts([meta(mintA, false), meta(basketVault, true), meta(makerAtaA, true)])
+        .signers([taker])
+        .rpc();
+
+    try {
+      await crank();
+      expect.fail("crank should have failed");
+    } catch (err) {
+      expect((err as anchor.AnchorError).error.errorCode.code).to.equal("OfferNotExpired");
+    }
+
+    await new Promise(resolve => setTimeout(resolve, 5000));
+
+    const makerBalanceABefore = (await provider.connection.getTokenAccountBalance(makerAtaA)).value.uiAmount;
+    await crank();
+
+    expect(await provider.connection.getAccountInfo(basket)).to.be.null;
+    const makerBalanceA = (await provider.connection.getTokenAccountBalance(makerAtaA)).value.uiAmount;
+    expect(makerBalanceA - makerBalanceABefore).to.equal(10);
+  });
+
+  describe("validation", () => {
+    const seed6 = new anchor.BN(6666);
+    let mintC: anchor.web3.PublicKey;
+
+    const expectAnchorError = async (promise: Promise<unknown>, code: string) => {
+      try {
+        await promise;
+        expect.fail(`expected ${code}`);
+      } catch (err) {
+        expect((err as anchor.AnchorError).error.errorCode.code).to.equal(code);
+      }
+    };
+
+    const makeAccounts = (seed: anchor.BN, mintAKey: anchor.web3.PublicKey, mintBKey: anchor.web3.PublicKey) => {
+      const [escrow] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("escrow"), maker.toBuffer(), seed.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      return {
+        maker: maker,
+        payer: maker,
+        mintA: mintAKey,
+        mintB: mintBKey,
+        makerAtaA: getAssociatedTokenAddressSync(mintAKey, maker),
+        escrow,
+        vault: getAssociatedTokenAddressSync(mintAKey, escrow, true),
+        protocol: protocolPda,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      };
+    };
+
+    before(async () => {
+      mintC = await createMint(provider.connection, provider.wallet.payer, maker, null, 0);
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          createAssociatedTokenAccountInstruction(maker, getAssociatedTokenAddressSync(mintC, maker), maker, mintC),
+          createAssociatedTokenAccountInstruction(
+            maker,
+            getAssociatedTokenAddressSync(mintC, taker.publicKey),
+            taker.publicKey,
+            mintC
+          )
+        )
+      );
+
+      await program.methods
+        .make(seed6, new anchor.BN(depositAmount), new anchor.BN(receiveAmount), expiresAt, null, { fixed: {} })
+        .accountsStrict(makeAccounts(seed6, mintA, mintB))
+        .rpc();
+    });
+
+    it("Rejects a zero receive amount", async () => {
+      const seed = new anchor.BN(7001);
+      await expectAnchorError(
+        program.methods
+          .make(seed, new anchor.BN(depositAmount), new anchor.BN(0), expiresAt, null, { fixed: {} })
+          .accountsStrict(makeAccounts(seed, mintA, mintB))
+          .rpc(),
+        "InvalidAmount"
+      );
+    });
+
+    it("Rejects a zero deposit amount", async () => {
+      const seed = new anchor.BN(7002);
+      await expectAnchorError(
+        program.methods
+          .make(seed, new anchor.BN(0), new anchor.BN(receiveAmount), expiresAt, null, { fixed: {} })
+          .accountsStrict(makeAccounts(seed, mintA, mintB))
+          .rpc(),
+        "InvalidAmount"
+      );
+    });
+
+    it("Rejects identical mints", async () => {
+      const seed = new anchor.BN(7003);
+      await expectAnchorError(
+        program.methods
+          .make(seed, new anchor.BN(depositAmount), new anchor.BN(receiveAmount), expiresAt, null, { fixed: {} })
+          .accountsStrict(makeAccounts(seed, mintA, mintA))
+          .rpc(),
+        "IdenticalMints"
+      );
+    });
+
+    it("Rejects a take with the wrong maker", async () => {
+      const { escrow, vault } = makeAccounts(seed6, mintA, mintB);
+      await expectAnchorError(
+        program.methods
+          .take(new anchor.BN(depositAmount), new anchor.BN(receiveAmount), new anchor.BN(depositAmount), null)
+          .accountsStrict({
+            taker: taker.publicKey,
+            maker: taker.publicKey,
+            mintA: mintA,
+            mintB: mintB,
+            takerAtaA: getAssociatedTokenAddressSync(mintA, taker.publicKey),
+            takerAtaB: takerAtaB,
+            makerAtaB: takerAtaB,
+            escrow,
+            vault,
+            rentPayer: maker,
+            vaultRentPayer: maker,
+            protocol: protocolPda,
+            treasuryAtaB: null,
+            referrerAtaB: null,
+            oracle: null,
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([taker])
+          .rpc(),
+        "InvalidMaker"
+      );
+    });
+
+    it("Rejects a take with the wrong mint b", async () => {
+      const { escrow, vault } = makeAccounts(seed6, mintA, mintB);
+      await expectAnchorError(
+        program.methods
+          .take(new anchor.BN(depositAmount), new anchor.BN(receiveAmount), new anchor.BN(depositAmount), null)
+          .accountsStrict({
+            taker: taker.publicKey,
+            maker: maker,
+            mintA: mintA,
+            mintB: mintC,
+            takerAtaA: getAssociatedTokenAddressSync(mintA, taker.publicKey),
+            takerAtaB: getAssociatedTokenAddressSync(mintC, taker.publicKey),
+            makerAtaB: getAssociatedTokenAddressSync(mintC, maker),
+            escrow,
+            vault,
+            rentPayer: maker,
+            vaultRentPayer: maker,
+            protocol: protocolPda,
+            treasuryAtaB: null,
+            referrerAtaB: null,
+            oracle: null,
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([taker])
+          .rpc(),
+        "InvalidMintB"
+      );
+    });
+
+    it("Rejects a take when the terms differ from what the taker expected", async () => {
+      const { escrow, vault } = makeAccounts(seed6, mintA, mintB);
+      await expectAnchorError(
+        program.methods
+          .take(new anchor.BN(depositAmount), new anchor.BN(receiveAmount - 1), new anchor.BN(depositAmount), null)
+          .accountsStrict({
+            taker: taker.publicKey,
+            maker: maker,
+            mintA: mintA,
+            mintB: mintB,
+            takerAtaA: getAssociatedTokenAddressSync(mintA, taker.publicKey),
+            takerAtaB: takerAtaB,
+            makerAtaB: getAssociatedTokenAddressSync(mintB, maker),
+            escrow,
+            vault,
+            rentPayer: maker,
+            vaultRentPayer: maker,
+            protocol: protocolPda,
+            treasuryAtaB: null,
+            referrerAtaB: null,
+            oracle: null,
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([taker])
+          .rpc(),
+        "TermsChanged"
+      );
+    });
+
+    it("Rejects a refund with the wrong mint a", async () => {
+      const { escrow, vault } = makeAccounts(seed6, mintA, mintB);
+      await expectAnchorError(
+        program.methods
+          .refund()
+          .accountsStrict({
+            maker: maker,
+            mintA: mintC,
+            makerAtaA: getAssociatedTokenAddressSync(mintC, maker),
+            escrow,
+            vault,
+            rentPayer: maker,
+            vaultRentPayer: maker,
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .rpc(),
+        "InvalidMintA"
+      );
+    });
+  });
+
+  describe("protocol fees", () => {
+    const seed7 = new anchor.BN(7777);
+    const treasury = anchor.web3.Keypair.generate();
+    const referrer = anchor.web3.Keypair.generate();
+    let treasuryAtaB: anchor.web3.PublicKey;
+    let referrerAtaB: anchor.web3.PublicKey;
+
+    const feeBps = 100;
+    const referralBps = 5000;
+    const feeReceive = 1000;
+
+    before(async () => {
+      treasuryAtaB = getAssociatedTokenAddressSync(mintB, treasury.publicKey);
+      referrerAtaB = getAssociatedTokenAddressSync(mintB, referrer.publicKey);
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          createAssociatedTokenAccountInstruction(maker, treasuryAtaB, treasury.publicKey, mintB),
+          createAssociatedTokenAccountInstruction(maker, referrerAtaB, referrer.publicKey, mintB)
+        )
+      );
+      await mintTo(provider.connection, provider.wallet.payer, mintA, makerAtaA, provider.wallet.payer, depositAmount);
+      await mintTo(provider.connection, taker, mintB, takerAtaB, taker, feeReceive);
+
+      const [programData] = anchor.web3.PublicKey.findProgramAddressSync(
+        [program.programId.toBuffer()],
+        new anchor.web3.PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+      );
+
+      // Only the program's upgrade authority can create the config
+      try {
+        await program.methods
+          .initializeProtocol(taker.publicKey, 1000, 0)
+          .accountsStrict({
+            authority: taker.publicKey,
+            protocol: protocolPda,
+            program: program.programId,
+            programData,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([taker])
+          .rpc();
+        expect.fail("initialize should have failed");
+      } catch (err) {
+        expect((err as anchor.AnchorError).error.errorCode.code).to.equal("InvalidAuthority");
+      }
+
+      await program.methods
+        .initializeProtocol(treasury.publicKey, feeBps, referralBps)
+        .accountsStrict({
+          authority: maker,
+          protocol: protocolPda,
+          program: program.programId,
+          programData,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+    });
+
+    after(async () => {
+      await program.methods
+        .updateProtocol(treasury.publicKey, 0, 0)
+        .accountsStrict({ authority: maker, protocol: protocolPda })
+        .rpc();
+    });
+
+    it("Routes the fee to the treasury and shares it with the referrer", async () => {
+      [escrowPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("escrow"), maker.toBuffer(), seed7.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      vault = getAssociatedTokenAddressSync(mintA, escrowPda, true);
+
+      await program.methods
+        .make(seed7, new anchor.BN(depositAmount), new anchor.BN(feeReceive), expiresAt, null, { fixed: {} })
+        .accountsStrict({
+          maker: maker,
+          payer: maker,
+          mintA: mintA,
+          mintB: mintB,
+          makerAtaA: makerAtaA,
+          escrow: escrowPda,
+          vault: vault,
+          protocol: protocolPda,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      const makerBalanceBBefore = (await provider.connection.getTokenAccountBalance(makerAtaB)).value.uiAmount;
+
+      await program.methods
+        .take(new anchor.BN(depositAmount), new anchor.BN(feeReceive), new anchor.BN(depositAmount), null)
+        .accountsStrict({
+          taker: taker.publicKey,
+          maker: maker,
+          mintA: mintA,
+          mintB: mintB,
+          takerAtaA: takerAtaA,
+          takerAtaB: takerAtaB,
+          makerAtaB: makerAtaB,
+          escrow: escrowPda,
+          vault: vault,
+          rentPayer: maker,
+          vaultRentPayer: maker,
+          protocol: protocolPda,
+          treasuryAtaB,
+          referrerAtaB,
+          oracle: null,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([taker])
+        .rpc();
+
+      const fee = (feeReceive * feeBps) / 10_000;
+      const referral = (fee * referralBps) / 10_000;
+
+      const makerBalanceB = (await provider.connection.getTokenAccountBalance(makerAtaB)).value.uiAmount;
+      expect(makerBalanceB - makerBalanceBBefore).to.equal(feeReceive - fee);
+
+      const treasuryBalance = (await provider.connection.getTokenAccountBalance(treasuryAtaB)).value.uiAmount;
+      expect(treasuryBalance).to.equal(fee - referral);
+
+      const referrerBalance = (await provider.connection.getTokenAccountBalance(referrerAtaB)).value.uiAmount;
+      expect(referrerBalance).to.equal(referral);
+    });
+
+    it("Charges the fee the offer was made with, not a later one", async () => {
+      const seed = new anchor.BN(7778);
+      const [escrow] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("escrow"), maker.toBuffer(), seed.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const escrowVault = getAssociatedTokenAddressSync(mintA, escrow, true);
+
+      await mintTo(provider.connection, provider.wallet.payer, mintA, makerAtaA, provider.wallet.payer, depositAmount);
+      await mintTo(provider.connection, taker, mintB, takerAtaB, taker, feeReceive);
+
+      await program.methods
+        .make(seed, new anchor.BN(depositAmount), new anchor.BN(feeReceive), expiresAt, null, { fixed: {} })
+        .accountsStrict({
+          maker: maker,
+          payer: maker,
+          mintA: mintA,
+          mintB: mintB,
+          makerAtaA: makerAtaA,
+          escrow,
+          vault: escrowVault,
+          protocol: protocolPda,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      const escrowAccount = await program.account.escrow.fetch(escrow);
+      expect(escrowAccount.feeBps).to.equal(feeBps);
+      expect(escrowAccount.referralBps).to.equal(referralBps);
+
+      // Raising the fee afterwards does not touch the open offer
+      await program.methods
+        .updateProtocol(treasury.publicKey, 1000, referralBps)
+        .accountsStrict({ authority: maker, protocol: protocolPda })
+        .rpc();
+
+      const makerBalanceBBefore = (await provider.connection.getTokenAccountBalance(makerAtaB)).value.uiAmount;
+      const treasuryBalanceBefore = (await provider.connection.getTokenAccountBalance(treasuryAtaB)).value.uiAmount;
+
+      await program.methods
+        .take(new anchor.BN(depositAmount), new anchor.BN(feeReceive), new anchor.BN(depositAmount), null)
+        .accountsStrict({
+          taker: taker.publicKey,
+          maker: maker,
+          mintA: mintA,
+          mintB: mintB,
+          takerAtaA: takerAtaA,
+          takerAtaB: takerAtaB,
+          makerAtaB: makerAtaB,
+          escrow,
+          vault: escrowVault,
+          rentPayer: maker,
+          vaultRentPayer: maker,
+          protocol: protocolPda,
+          treasuryAtaB,
+          referrerAtaB: null,
+          oracle: null,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([taker])
+        .rpc();
+
+      const fee = (feeReceive * feeBps) / 10_000;
+
+      const makerBalanceB = (await provider.connection.getTokenAccountBalance(makerAtaB)).value.uiAmount;
+      expect(makerBalanceB - makerBalanceBBefore).to.equal(feeReceive - fee);
+
+      const treasuryBalance = (await provider.connection.getTokenAccountBalance(treasuryAtaB)).value.uiAmount;
+      expect(treasuryBalance - treasuryBalanceBefore).to.equal(fee);
+    });
+  });
+
+  describe("oracle pricing", () => {
+    const seed9 = new anchor.BN(9999);
+    // Loaded into the local validator from tests/fixtures/mock-price-feed.json:
+    // a Pyth-layout account quoting 2.5 mint_b per mint_a.
+    const feed = new anchor.web3.PublicKey("6PpnM46LqShSUkQJrXDyEY3otw7Pr2q45ZBomYZ8rZqT");
+    const premiumBps = 200;
+    const quoted = Math.ceil(depositAmount * 2.5 * (1 + premiumBps / 10_000));
+
+    let takeAccounts;
+
+    before(async () => {
+      [escrowPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("escrow"), maker.toBuffer(), seed9.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      vault = getAssociatedTokenAddressSync(mintA, escrowPda, true);
+
+      await mintTo(provider.connection, provider.wallet.payer, mintA, makerAtaA, provider.wallet.payer, depositAmount);
+      await mintTo(provider.connection, taker, mintB, takerAtaB, taker, quoted);
+
+      // `receive` is the maker's floor for oracle-priced offers
+      await program.methods
+        .make(seed9, new anchor.BN(depositAmount), new anchor.BN(1), expiresAt, null, {
+          oracle: { feed, premiumBps, maxStaleness: 0xffffffff, maxConfidenceBps: 100 },
+        })
+        .accountsStrict({
+          maker: maker,
+          payer: maker,
+          mintA: mintA,
+          mintB: mintB,
+          makerAtaA: makerAtaA,
+          escrow: escrowPda,
+          vault: vault,
+          protocol: protocolPda,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      takeAccounts = {
+        taker: taker.publicKey,
+        maker: maker,
+        mintA: mintA,
+        mintB: mintB,
+        takerAtaA: takerAtaA,
+        takerAtaB: takerAtaB,
+        makerAtaB: makerAtaB,
+        escrow: escrowPda,
+        vault: vault,
+        rentPayer: maker,
+        vaultRentPayer: maker,
+        protocol: protocolPda,
+        treasuryAtaB: null,
+        referrerAtaB: null,
+        oracle: feed,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      };
+    });
+
+    it("Rejects a take when the oracle price is above the taker's limit", async () => {
+      try {
+        await program.methods
+          .take(new anchor.BN(depositAmount), new anchor.BN(quoted - 1), new anchor.BN(depositAmount), null)
+          .accountsStrict(takeAccounts)
+          .signers([taker])
+          .rpc();
+        expect.fail("take should have failed");
+      } catch (err) {
+        expect((err as anchor.AnchorError).error.errorCode.code).to.equal("PriceAboveLimit");
+      }
+    });
+
+    it("Charges the oracle price plus the premium", async () => {
+      const makerBalanceBBefore = (await provider.connection.getTokenAccountBalance(makerAtaB)).value.uiAmount;
+
+      await program.methods
+        .take(new anchor.BN(depositAmount), new anchor.BN(quoted), new anchor.BN(depositAmount), null)
+        .accountsStrict(takeAccounts)
+        .signers([taker])
+        .rpc();
+
+      const makerBalanceB = (await provider.connection.getTokenAccountBalance(makerAtaB)).value.uiAmount;
+      expect(makerBalanceB - makerBalanceBBefore).to.equal(quoted);
+
+      const escrowInfo = await provider.connection.getAccountInfo(escrowPda);
+      expect(escrowInfo).to.be.null;
+    });
+
+    // The fixture was published at 1_760_000_000 with a confidence band of
+    // 4 bps of the price, so tighter bounds make it unusable.
+    const takeGuardedOffer = async (seed: anchor.BN, maxStaleness: number, maxConfidenceBps: number) => {
+      const [escrow] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("escrow"), maker.toBuffer(), seed.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const escrowVault = getAssociatedTokenAddressSync(mintA, escrow, true);
+
+      await mintTo(provider.connection, provider.wallet.payer, mintA, makerAtaA, provider.wallet.payer, depositAmount);
+
+      await program.methods
+        .make(seed, new anchor.BN(depositAmount), new anchor.BN(1), expiresAt, null, {
+          oracle: { feed, premiumBps, maxStaleness, maxConfidenceBps },
+        })
+        .accountsStrict({
+          maker: maker,
+          payer: maker,
+          mintA: mintA,
+          mintB: mintB,
+          makerAtaA: makerAtaA,
+          escrow,
+          vault: escrowVault,
+          protocol: protocolPda,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      await program.methods
+        .take(new anchor.BN(depositAmount), new anchor.BN(quoted), new anchor.BN(depositAmount), null)
+        .accountsStrict({ ...takeAccounts, escrow, vault: escrowVault })
+        .signers([taker])
+        .rpc();
+    };
+
+    it("Rejects a take when the oracle price is older than the offer allows", async () => {
+      try {
+        await takeGuardedOffer(new anchor.BN(9997), 60, 100);
+        expect.fail("take should have failed");
+      } catch (err) {
+        expect((err as anchor.AnchorError).error.errorCode.code).to.equal("StalePrice");
+      }
+    });
+
+    it("Rejects a take when the oracle confidence band is wider than the offer allows", async () => {
+      try {
+        await takeGuardedOffer(new anchor.BN(9998), 0xffffffff, 1);
+        expect.fail("take should have failed");
+      } catch (err) {
+        expect((err as anchor.AnchorError).error.errorCode.code).to.equal("PriceUncertain");
+      }
+    });
+  });
+
+  describe("dutch auctions", () => {
+    const startReceive = 400;
+    const endReceive = 100;
+    const duration = 10_000;
+
+    const makeAuction = async (seed: anchor.BN, startTime: number, curve: object) => {
+      const [escrow] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("escrow"), maker.toBuffer(), seed.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const escrowVault = getAssociatedTokenAddressSync(mintA, escrow, true);
+
+      await mintTo(provider.connection, provider.wallet.payer, mintA, makerAtaA, provider.wallet.payer, depositAmount);
+      await mintTo(provider.connection, taker, mintB, takerAtaB, taker, startReceive);
+
+      await program.methods
+        .make(seed, new anchor.BN(depositAmount), new anchor.BN(endReceive), expiresAt, null, {
+          dutchAuction: {
+            startReceive: new anchor.BN(startReceive),
+            endReceive: new anchor.BN(endReceive),
+            startTime: new anchor.BN(startTime),
+            duration,
+            curve,
+          },
+        })
+        .accountsStrict({
+          maker: maker,
+          payer: maker,
+          mintA: mintA,
+          mintB: mintB,
+          makerAtaA: makerAtaA,
+          escrow,
+          vault: escrowVault,
+          protocol: protocolPda,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      return { escrow, escrowVault };
+    };
+
+    const takeAuction = async (escrow: anchor.web3.PublicKey, escrowVault: anchor.web3.PublicKey) => {
+      const makerBalanceBBefore = (await provider.connection.getTokenAccountBalance(makerAtaB)).value.uiAmount;
+
+      // The taker accepts anything up to the starting price
+      await program.methods
+        .take(new anchor.BN(depositAmount), new anchor.BN(startReceive), new anchor.BN(depositAmount), null)
+        .accountsStrict({
+          taker: taker.publicKey,
+          maker: maker,
+          mintA: mintA,
+          mintB: mintB,
+          takerAtaA: takerAtaA,
+          takerAtaB: takerAtaB,
+          makerAtaB: makerAtaB,
+          escrow,
+          vault: escrowVault,
+          rentPayer: maker,
+          vaultRentPayer: maker,
+          protocol: protocolPda,
+          treasuryAtaB: null,
+          referrerAtaB: null,
+          oracle: null,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([taker])
+        .rpc();
+
+      const makerBalanceB = (await provider.connection.getTokenAccountBalance(makerAtaB)).value.uiAmount;
+      return makerBalanceB - makerBalanceBBefore;
+    };
+
+    it("Charges the end price once a linear auction is over", async () => {
+      const startTime = Math.floor(Date.now() / 1000) - 2 * duration;
+      const { escrow, escrowVault } = await makeAuction(new anchor.BN(10101), startTime, { linear: {} });
+
+      expect(await takeAuction(escrow, escrowVault)).to.equal(endReceive);
+    });
+
+    it("Charges the decayed price halfway through an exponential auction", async () => {
+      const startTime = Math.floor(Date.now() / 1000) - duration / 2;
+      const { escrow, escrowVault } = await makeAuction(new anchor.BN(10102), startTime, { exponential: {} });
+
+      // 400 * (100 / 400)^(1/2) = 200, give or take the validator clock
+      const paid = await takeAuction(escrow, escrowVault);
+      expect(paid).to.be.within(195, 205);
+    });
+  });
+
+  describe("bids", () => {
+    const seed10 = new anchor.BN(10201);
+    const bidDeposit = 300;
+    const bidReceive = 150;
+
+    it("Fills a bid in units of the wanted mint", async () => {
+      const [escrow] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("escrow"), maker.toBuffer(), seed10.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const escrowVault = getAssociatedTokenAddressSync(mintA, escrow, true);
+
+      await mintTo(provider.connection, provider.wallet.payer, mintA, makerAtaA, provider.wallet.payer, bidDeposit);
+      await mintTo(provider.connection, taker, mintB, takerAtaB, taker, bidReceive);
+
+      // The maker locks 300 of mint A to buy 150 of mint B
+      await program.methods
+        .makeBid(seed10, new anchor.BN(bidDeposit), new anchor.BN(bidReceive), expiresAt, null)
+        .accountsStrict({
+          maker: maker,
+          payer: maker,
+          mintA: mintA,
+          mintB: mintB,
+          makerAtaA: makerAtaA,
+          escrow,
+          vault: escrowVault,
+          protocol: protocolPda,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      const escrowAccount = await program.account.escrow.fetch(escrow);
+      expect(escrowAccount.side).to.deep.equal({ bid: {} });
+      expect(escrowAccount.remaining.toNumber()).to.equal(bidReceive);
+
+      const takeAccounts = {
+        taker: taker.publicKey,
+        maker: maker,
+        mintA: mintA,
+        mintB: mintB,
+        takerAtaA: takerAtaA,
+        takerAtaB: takerAtaB,
+        makerAtaB: makerAtaB,
+        escrow,
+        vault: escrowVault,
+        rentPayer: maker,
+        vaultRentPayer: maker,
+        protocol: protocolPda,
+        treasuryAtaB: null,
+        referrerAtaB: null,
+        oracle: null,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      };
+
+      const takerBalanceABefore = (await provider.connection.getTokenAccountBalance(takerAtaA)).value.uiAmount;
+      const makerBalanceBBefore = (await provider.connection.getTokenAccountBalance(makerAtaB)).value.uiAmount;
+
+      // Deliver a third of the wanted mint B, then the rest
+      await program.methods
+        .take(new anchor.BN(50), new anchor.BN(bidReceive), new anchor.BN(bidDeposit), null)
+        .accountsStrict(takeAccounts)
+        .signers([taker])
+        .rpc();
+
+      let takerBalanceA = (await provider.connection.getTokenAccountBalance(takerAtaA)).value.uiAmount;
+      expect(takerBalanceA - takerBalanceABefore).to.equal(100);
+
+      await program.methods
+        .take(new anchor.BN(100), new anchor.BN(bidReceive), new anchor.BN(bidDeposit - 100), null)
+        .accountsStrict(takeAccounts)
+        .signers([taker])
+        .rpc();
+
+      takerBalanceA = (await provider.connection.getTokenAccountBalance(takerAtaA)).value.uiAmount;
+      expect(takerBalanceA - takerBalanceABefore).to.equal(bidDeposit);
+
+      const makerBalanceB = (await provider.connection.getTokenAccountBalance(makerAtaB)).value.uiAmount;
+      expect(makerBalanceB - makerBalanceBBefore).to.equal(bidReceive);
+
+      const escrowInfo = await provider.connection.getAccountInfo(escrow);
+      expect(escrowInfo).to.be.null;
+    });
+  });
+
+  describe("batch takes", () => {
+    const seeds = [new anchor.BN(10401), new anchor.BN(10402)];
+
+    it("Sweeps asks in order until the budget is spent", async () => {
+      const escrows = seeds.map((s) => {
+        const [escrow] = anchor.web3.PublicKey.findProgramAddressSync(
+          [Buffer.from("escrow"), maker.toBuffer(), s.toArrayLike(Buffer, "le", 8)],
+          program.programId
+        );
+        return { escrow, vault: getAssociatedTokenAddressSync(mintA, escrow, true) };
+      });
+
+      await mintTo(provider.connection, provider.wallet.payer, mintA, makerAtaA, provider.wallet.payer, 200);
+      await mintTo(provider.connection, taker, mintB, takerAtaB, taker, 350);
+
+      // Two asks of 100 mint A, at 2 and 3 mint B apiece
+      for (const [i, receive] of [200, 300].entries()) {
+        await program.methods
+          .make(seeds[i], new anchor.BN(100), new anchor.BN(receive), expiresAt, null, { fixed: {} })
+          .accountsStrict({
+            maker: maker,
+            payer: maker,
+            mintA: mintA,
+            mintB: mintB,
+            makerAtaA: makerAtaA,
+            escrow: escrows[i].escrow,
+            vault: escrows[i].vault,
+            protocol: protocolPda,
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .rpc();
+      }
+
+      const remainingAccounts = escrows.flatMap(({ escrow, vault }) => [
+        { pubkey: escrow, isSigner: false, isWritable: true },
+        { pubkey: vault, isSigner: false, isWritable: true },
+        { pubkey: maker, isSigner: false, isWritable: true },
+        { pubkey: makerAtaB, isSigner: false, isWritable: true },
+        { pubkey: maker, isSigner: false, isWritable: true },
+        { pubkey: maker, isSigner: false, isWritable: true },
+      ]);
+
+      const takerBalanceABefore = (await provider.connection.getTokenAccountBalance(takerAtaA)).value.uiAmount;
+      const makerBalanceBBefore = (await provider.connection.getTokenAccountBalance(makerAtaB)).value.uiAmount;
+
+      // 350 mint B buys all of the first ask and 50 of the second
+      await program.methods
+        .takeMany(new anchor.BN(350), new anchor.BN(150))
+        .accountsStrict({
+          taker: taker.publicKey,
+          mintA: mintA,
+          mintB: mintB,
+          takerAtaA: takerAtaA,
+          takerAtaB: takerAtaB,
+          protocol: protocolPda,
+          treasuryAtaB: null,
+          referrerAtaB: null,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts(remainingAccounts)
+        .signers([taker])
+        .rpc();
+
+      const takerBalanceA = (await provider.connection.getTokenAccountBalance(takerAtaA)).value.uiAmount;
+      expect(takerBalanceA - takerBalanceABefore).to.equal(150);
+
+      const makerBalanceB = (await provider.connection.getTokenAccountBalance(makerAtaB)).value.uiAmount;
+      expect(makerBalanceB - makerBalanceBBefore).to.equal(350);
+
+      expect(await provider.connection.getAccountInfo(escrows[0].escrow)).to.be.null;
+      const second = await program.account.escrow.fetch(escrows[1].escrow);
+      expect(second.remaining.toNumber()).to.equal(50);
+    });
+
+    it("Rejects a sweep that fills less than the taker's minimum", async () => {
+      const [escrow] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("escrow"), maker.toBuffer(), seeds[1].toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+
+      await mintTo(provider.connection, taker, mintB, takerAtaB, taker, 30);
+
+      try {
+        await program.methods
+          .takeMany(new anchor.BN(30), new anchor.BN(11))
+          .accountsStrict({
+            taker: taker.publicKey,
+            mintA: mintA,
+            mintB: mintB,
+            takerAtaA: takerAtaA,
+            takerAtaB: takerAtaB,
+            protocol: protocolPda,
+            treasuryAtaB: null,
+            referrerAtaB: null,
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .remainingAccounts([
+            { pubkey: escrow, isSigner: false, isWritable: true },
+            { pubkey: getAssociatedTokenAddressSync(mintA, escrow, true), isSigner: false, isWritable: true },
+            { pubkey: maker, isSigner: false, isWritable: true },
+            { pubkey: makerAtaB, isSigner: false, isWritable: true },
+            { pubkey: maker, isSigner: false, isWritable: true },
+            { pubkey: maker, isSigner: false, isWritable: true },
+          ])
+          .signers([taker])
+          .rpc();
+        expect.fail("take should have failed");
+      } catch (err) {
+        expect((err as anchor.AnchorError).error.errorCode.code).to.equal("InsufficientFill");
+      }
+    });
+  });
+
+  describe("arbitration", () => {
+    const arbiter = anchor.web3.Keypair.generate();
+    const payment = 1000;
+
+    const arbitratedAccounts = (seed: anchor.BN) => {
+      const [arbitrated] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("arbitrated"), maker.toBuffer(), seed.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      return { arbitrated, vault: getAssociatedTokenAddressSync(mintA, arbitrated, true) };
+    };
+
+    const makeArbitrated = async (seed: anchor.BN, reviewPeriod: number) => {
+      const { arbitrated, vault } = arbitratedAccounts(seed);
+      await mintTo(provider.connection, provider.wallet.payer, mintA, makerAtaA, provider.wallet.payer, payment);
+      await program.methods
+        .makeArbitrated(seed, new anchor.BN(payment), taker.publicKey, arbiter.publicKey, reviewPeriod, 3600, 5000)
+        .accountsStrict({
+          maker: maker,
+          mint: mintA,
+          makerAta: makerAtaA,
+          arbitrated,
+          vault,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      await program.methods
+        .markDelivered()
+        .accountsStrict({ beneficiary: taker.publicKey, arbitrated })
+        .signers([taker])
+        .rpc();
+
+      return {
+        maker: maker,
+        beneficiary: taker.publicKey,
+        mint: mintA,
+        makerAta: makerAtaA,
+        beneficiaryAta: takerAtaA,
+        arbitrated,
+        vault,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      };
+    };
+
+    it("Lets the arbiter split a disputed payment", async () => {
+      const accounts = await makeArbitrated(new anchor.BN(10501), 3600);
+
+      await program.methods
+        .dispute()
+        .accountsStrict({ maker: maker, arbitrated: accounts.arbitrated })
+        .rpc();
+
+      const arbitrated = await program.account.arbitratedEscrow.fetch(accounts.arbitrated);
+      expect(arbitrated.status).to.deep.equal({ disputed: {} });
+
+      const makerBalanceBefore = (await provider.connection.getTokenAccountBalance(makerAtaA)).value.uiAmount;
+      const beneficiaryBalanceBefore = (await provider.connection.getTokenAccountBalance(takerAtaA)).value.uiAmount;
+
+      // The maker can't settle the dispute in their own favour
+      try {
+        await program.methods
+          .resolveDispute(0)
+          .accountsStrict({ authority: maker, ...accounts })
+          .rpc();
+        expect.fail("resolve should have failed");
+      } catch (err) {
+        expect((err as anchor.AnchorError).error.errorCode.code).to.equal("InvalidArbiter");
+      }
+
+      await program.methods
+        .resolveDispute(6000)
+        .accountsStrict({ authority: arbiter.publicKey, ...accounts })
+        .signers([arbiter])
+        .rpc();
+
+      const makerBalance = (await provider.connection.getTokenAccountBalance(makerAtaA)).value.uiAmount;
+      expect(makerBalance - makerBalanceBefore).to.equal(400);
+      const beneficiaryBalance = (await provider.connection.getTokenAccountBalance(takerAtaA)).value.uiAmount;
+      expect(beneficiaryBalance - beneficiaryBalanceBefore).to.equal(600);
+
+      expect(await provider.connection.getAccountInfo(accounts.arbitrated)).to.be.null;
+      expect(await provider.connection.getAccountInfo(accounts.vault)).to.be.null;
+    });
+
+    it("Pays the beneficiary once the maker lets the review period lapse", async () => {
+      const accounts = await makeArbitrated(new anchor.BN(10502), 1);
+
+      const beneficiaryBalanceBefore = (await provider.connection.getTokenAccountBalance(takerAtaA)).value.uiAmount;
+      await new Promise(resolve => setTimeout(resolve, 2000));
+
+      // The beneficiary cranks the settlement without the maker
+      await program.methods
+        .settleTimeout()
+        .accountsStrict({ authority: taker.publicKey, ...accounts })
+        .signers([taker])
+        .rpc();
+
+      const beneficiaryBalance = (await provider.connection.getTokenAccountBalance(takerAtaA)).value.uiAmount;
+      expect(beneficiaryBalance - beneficiaryBalanceBefore).to.equal(payment);
+      expect(await provider.connection.getAccountInfo(accounts.arbitrated)).to.be.null;
+    });
+  });
+
+  describe("milestones", () => {
+    const seed11 = new anchor.BN(10601);
+    const milestoneArbiter = anchor.web3.Keypair.generate();
+
+    it("Releases milestones in order and sends a disputed one to the arbiter", async () => {
+      const [milestones] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("milestones"), maker.toBuffer(), seed11.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const milestoneVault = getAssociatedTokenAddressSync(mintA, milestones, true);
+
+      await mintTo(provider.connection, provider.wallet.payer, mintA, makerAtaA, provider.wallet.payer, 1000);
+
+      const now = Math.floor(Date.now() / 1000);
+      await program.methods
+        .makeMilestones(
+          seed11,
+          [
+            { recipient: taker.publicKey, amount: new anchor.BN(100), deadline: new anchor.BN(now + 2) },
+            { recipient: taker.publicKey, amount: new anchor.BN(200), deadline: new anchor.BN(now + 3600) },
+            { recipient: taker.publicKey, amount: new anchor.BN(300), deadline: new anchor.BN(now + 7200) },
+            { recipient: taker.publicKey, amount: new anchor.BN(400), deadline: new anchor.BN(now + 10800) },
+          ],
+          milestoneArbiter.publicKey,
+          3600,
+          5000
+        )
+        .accountsStrict({
+          maker: maker,
+          mint: mintA,
+          makerAta: makerAtaA,
+          milestones,
+          vault: milestoneVault,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      const releaseAccounts = {
+        maker: maker,
+        recipient: taker.publicKey,
+        mint: mintA,
+        recipientAta: takerAtaA,
+        milestones,
+        vault: milestoneVault,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      };
+
+      const recipientBalanceBefore = (await provider.connection.getTokenAccountBalance(takerAtaA)).value.uiAmount;
+
+      // The first milestone pays out on its own once its deadline passes
+      await new Promise(resolve => setTimeout(resolve, 3000));
+      await program.methods
+        .claimMilestone()
+        .accountsStrict({ authority: taker.publicKey, ...releaseAccounts })
+        .signers([taker])
+        .rpc();
+
+      // The second one is not due yet
+      try {
+        await program.methods
+          .claimMilestone()
+          .accountsStrict({ authority: taker.publicKey, ...releaseAccounts })
+          .signers([taker])
+          .rpc();
+        expect.fail("claim should have failed");
+      } catch (err) {
+        expect((err as anchor.AnchorError).error.errorCode.code).to.equal("TimeoutNotReached");
+      }
+
+      // The maker approves it early, then disputes the third
+      await program.methods
+        .approveMilestone()
+        .accountsStrict({ authority: maker, ...releaseAccounts })
+        .rpc();
+
+      await program.methods
+        .disputeMilestone()
+        .accountsStrict({ maker: maker, milestones })
+        .rpc();
+
+      const recipientBalance = (await provider.connection.getTokenAccountBalance(takerAtaA)).value.uiAmount;
+      expect(recipientBalance - recipientBalanceBefore).to.equal(300);
+
+      const makerBalanceBefore = (await provider.connection.getTokenAccountBalance(makerAtaA)).value.uiAmount;
+
+      // Cancelling only returns the milestones after the disputed one
+      await program.methods
+        .cancelMilestones()
+        .accountsStrict({
+          maker: maker,
+          mint: mintA,
+          makerAta: makerAtaA,
+          milestones,
+          vault: milestoneVault,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+      const makerBalanceAfterCancel = (await provider.connection.getTokenAccountBalance(makerAtaA)).value.uiAmount;
+      expect(makerBalanceAfterCancel - makerBalanceBefore).to.equal(400);
+
+      const settleAccounts = {
+        maker: maker,
+        recipient: taker.publicKey,
+        mint: mintA,
+        makerAta: makerAtaA,
+        recipientAta: takerAtaA,
+        milestones,
+        vault: milestoneVault,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      };
+
+      // Only the arbiter rules on the disputed milestone
+      try {
+        await program.methods
+          .resolveMilestone(0)
+          .accountsStrict({ authority: maker, ...settleAccounts })
+          .rpc();
+        expect.fail("resolve should have failed");
+      } catch (err) {
+        expect((err as anchor.AnchorError).error.errorCode.code).to.equal("InvalidArbiter");
+      }
+
+      await program.methods
+        .resolveMilestone(5000)
+        .accountsStrict({ authority: milestoneArbiter.publicKey, ...settleAccounts })
+        .signers([milestoneArbiter])
+        .rpc();
+
+      const recipientBalanceAfter = (await provider.connection.getTokenAccountBalance(takerAtaA)).value.uiAmount;
+      expect(recipientBalanceAfter - recipientBalance).to.equal(150);
+      const makerBalance = (await provider.connection.getTokenAccountBalance(makerAtaA)).value.uiAmount;
+      expect(makerBalance - makerBalanceAfterCancel).to.equal(150);
+
+      expect(await provider.connection.getAccountInfo(milestones)).to.be.null;
+      expect(await provider.connection.getAccountInfo(milestoneVault)).to.be.null;
+    });
+
+    it("Settles a disputed milestone by the fallback split once the arbiter is silent", async () => {
+      const seedTimeout = new anchor.BN(10603);
+      const [milestones] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("milestones"), maker.toBuffer(), seedTimeout.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const milestoneVault = getAssociatedTokenAddressSync(mintA, milestones, true);
+
+      await mintTo(provider.connection, provider.wallet.payer, mintA, makerAtaA, provider.wallet.payer, 100);
+
+      await program.methods
+        .makeMilestones(
+          seedTimeout,
+          [
+            {
+              recipient: taker.publicKey,
+              amount: new anchor.BN(100),
+              deadline: new anchor.BN(Math.floor(Date.now() / 1000) + 3600),
+            },
+          ],
+          milestoneArbiter.publicKey,
+          1,
+          10000
+        )
+        .accountsStrict({
+          maker: maker,
+          mint: mintA,
+          makerAta: makerAtaA,
+          milestones,
+          vault: milestoneVault,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      await program.methods
+        .disputeMilestone()
+        .accountsStrict({ maker: maker, milestones })
+        .rpc();
+
+      // With no later milestones there is nothing for the maker to cancel
+      try {
+        await program.methods
+          .cancelMilestones()
+          .accountsStrict({
+            maker: maker,
+            mint: mintA,
+            makerAta: makerAtaA,
+            milestones,
+            vault: milestoneVault,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .rpc();
+        expect.fail("cancel should have failed");
+      } catch (err) {
+        expect((err as anchor.AnchorError).error.errorCode.code).to.equal("InvalidStatus");
+      }
+
+      const recipientBalanceBefore = (await provider.connection.getTokenAccountBalance(takerAtaA)).value.uiAmount;
+
+      await new Promise(resolve => setTimeout(resolve, 3000));
+      await program.methods
+        .settleMilestoneTimeout()
+        .accountsStrict({
+          authority: taker.publicKey,
+          maker: maker,
+          recipient: taker.publicKey,
+          mint: mintA,
+          makerAta: makerAtaA,
+          recipientAta: takerAtaA,
+          milestones,
+          vault: milestoneVault,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([taker])
+        .rpc();
+
+      const recipientBalance = (await provider.connection.getTokenAccountBalance(takerAtaA)).value.uiAmount;
+      expect(recipientBalance - recipientBalanceBefore).to.equal(100);
+
+      expect(await provider.connection.getAccountInfo(milestones)).to.be.null;
+      expect(await provider.connection.getAccountInfo(milestoneVault)).to.be.null;
+    });
+
+    it("Pays tokens donated to the vault out with the last milestone", async () => {
+      const seedDust = new anchor.BN(10602);
+      const [milestones] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("milestones"), maker.toBuffer(), seedDust.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const milestoneVault = getAssociatedTokenAddressSync(mintA, milestones, true);
+
+      await mintTo(provider.connection, provider.wallet.payer, mintA, makerAtaA, provider.wallet.payer, 100);
+
+      await program.methods
+        .makeMilestones(
+          seedDust,
+          [
+            {
+              recipient: taker.publicKey,
+              amount: new anchor.BN(100),
+              deadline: new anchor.BN(Math.floor(Date.now() / 1000) + 3600),
+            },
+          ],
+          milestoneArbiter.publicKey,
+          3600,
+          5000
+        )
+        .accountsStrict({
+          maker: maker,
+          mint: mintA,
+          makerAta: makerAtaA,
+          milestones,
+          vault: milestoneVault,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      await mintTo(provider.connection, provider.wallet.payer, mintA, milestoneVault, provider.wallet.payer, 1);
+
+      const recipientBalanceBefore = (await provider.connection.getTokenAccountBalance(takerAtaA)).value.uiAmount;
+
+      await program.methods
+        .approveMilestone()
+        .accountsStrict({
+          authority: maker,
+          maker: maker,
+          recipient: taker.publicKey,
+          mint: mintA,
+          recipientAta: takerAtaA,
+          milestones,
+          vault: milestoneVault,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      const recipientBalance = (await provider.connection.getTokenAccountBalance(takerAtaA)).value.uiAmount;
+      expect(recipientBalance - recipientBalanceBefore).to.equal(101);
+
+      expect(await provider.connection.getAccountInfo(milestones)).to.be.null;
+      expect(await provider.connection.getAccountInfo(milestoneVault)).to.be.null;
+    });
+  });
+
+  describe("vesting", () => {
+    const seed12 = new anchor.BN(10701);
+    const grant = 1000;
+
+    it("Claims the vested part of a grant and claws back the rest", async () => {
+      const [vesting] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("vesting"), maker.toBuffer(), seed12.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const vestingVault = getAssociatedTokenAddressSync(mintA, vesting, true);
+
+      await mintTo(provider.connection, provider.wallet.payer, mintA, makerAtaA, provider.wallet.payer, grant);
+
+      // Halfway through a 1000 second grant that unlocks every 100 seconds
+      const start = new anchor.BN(Math.floor(Date.now() / 1000) - 500);
+      await program.methods
+        .createVesting(seed12, taker.publicKey, new anchor.BN(grant), start, 100, 1000, 100, true)
+        .accountsStrict({
+          grantor: maker,
+          mint: mintA,
+          grantorAta: makerAtaA,
+          vesting,
+          vault: vestingVault,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      // Tokens sent to the vault from outside the grant go back to the grantor
+      await mintTo(provider.connection, provider.wallet.payer, mintA, vestingVault, provider.wallet.payer, 1);
+
+      const claimAccounts = {
+        beneficiary: taker.publicKey,
+        grantor: maker,
+        mint: mintA,
+        beneficiaryAta: takerAtaA,
+        // Only the claim that closes the schedule may need it
+        grantorAta: null,
+        vesting,
+        vault: vestingVault,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      };
+
+      const beneficiaryBalanceBefore = (await provider.connection.getTokenAccountBalance(takerAtaA)).value.uiAmount;
+      await program.methods.claimVested().accountsStrict(claimAccounts).signers([taker]).rpc();
+
+      const schedule = await program.account.vestingSchedule.fetch(vesting);
+      const claimed = schedule.claimed.toNumber();
+      expect(claimed).to.be.greaterThan(0).and.lessThan(grant);
+      expect(claimed % 100).to.equal(0);
+
+      const grantorBalanceBefore = (await provider.connection.getTokenAccountBalance(makerAtaA)).value.uiAmount;
+      await program.methods
+        .revokeVesting()
+        .accountsStrict({
+          grantor: maker,
+          mint: mintA,
+          grantorAta: makerAtaA,
+          vesting,
+          vault: vestingVault,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+      const grantorBalance = (await provider.connection.getTokenAccountBalance(makerAtaA)).value.uiAmount;
+
+      // Whatever vested before the revocation can still be claimed
+      const revoked = await program.account.vestingSchedule.fetchNullable(vesting);
+      if (revoked !== null) {
+        await program.methods
+          .claimVested()
+          .accountsStrict({ ...claimAccounts, grantorAta: makerAtaA })
+          .signers([taker])
+          .rpc();
+      }
+
+      const beneficiaryBalance = (await provider.connection.getTokenAccountBalance(takerAtaA)).value.uiAmount;
+      expect(beneficiaryBalance - beneficiaryBalanceBefore + grantorBalance - grantorBalanceBefore).to.equal(grant + 1);
+
+      expect(await provider.connection.getAccountInfo(vesting)).to.be.null;
+      expect(await provider.connection.getAccountInfo(vestingVault)).to.be.null;
+    });
+  });
+
+  describe("streams", () => {
+    const seed13 = new anchor.BN(10801);
+
+    it("Streams tokens, tops up a dry stream and settles it on cancel", async () => {
+      const [stream] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("stream"), maker.toBuffer(), seed13.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const streamVault = getAssociatedTokenAddressSync(mintA, stream, true);
+
+      await mintTo(provider.connection, provider.wallet.payer, mintA, makerAtaA, provider.wallet.payer, 1050);
+
+      // 1 token per second, started 100 seconds ago but only funded for 50
+      const now = Math.floor(Date.now() / 1000);
+      await program.methods
+        .createStream(seed13, taker.publicKey, new anchor.BN(1), new anchor.BN(now - 100), new anchor.BN(now + 3600), new anchor.BN(50))
+        .accountsStrict({
+          sender: maker,
+          mint: mintA,
+          senderAta: makerAtaA,
+          stream,
+          vault: streamVault,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      const recipientBalanceBefore = (await provider.connection.getTokenAccountBalance(takerAtaA)).value.uiAmount;
+
+      await program.methods
+        .withdrawStream()
+        .accountsStrict({
+          recipient: taker.publicKey,
+          mint: mintA,
+          recipientAta: takerAtaA,
+          stream,
+          vault: streamVault,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([taker])
+        .rpc();
+
+      let recipientBalance = (await provider.connection.getTokenAccountBalance(takerAtaA)).value.uiAmount;
+      expect(recipientBalance - recipientBalanceBefore).to.equal(50);
+
+      await program.methods
+        .topUpStream(new anchor.BN(1000))
+        .accountsStrict({
+          sender: maker,
+          mint: mintA,
+          senderAta: makerAtaA,
+          stream,
+          vault: streamVault,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+      const senderBalanceBefore = (await provider.connection.getTokenAccountBalance(makerAtaA)).value.uiAmount;
+
+      // The recipient cancels; the time the stream ran dry is paid out too
+      await program.methods
+        .cancelStream()
+        .accountsStrict({
+          authority: taker.publicKey,
+          sender: maker,
+          recipient: taker.publicKey,
+          mint: mintA,
+          senderAta: makerAtaA,
+          recipientAta: takerAtaA,
+          stream,
+          vault: streamVault,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([taker])
+        .rpc();
+
+      recipientBalance = (await provider.connection.getTokenAccountBalance(takerAtaA)).value.uiAmount;
+      const earned = recipientBalance - recipientBalanceBefore;
+      expect(earned).to.be.at.least(100).and.lessThan(1050);
+
+      const senderBalance = (await provider.connection.getTokenAccountBalance(makerAtaA)).value.uiAmount;
+      expect(senderBalance - senderBalanceBefore).to.equal(1050 - earned);
+
+      expect(await provider.connection.getAccountInfo(stream)).to.be.null;
+      expect(await provider.connection.getAccountInfo(streamVault)).to.be.null;
+    });
+  });
+
+  describe("rent payers", () => {
+    const seed14 = new anchor.BN(10901);
+    const relayer = anchor.web3.Keypair.generate();
+
+    before(async () => {
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(relayer.publicKey, anchor.web3.LAMPORTS_PER_SOL)
+      );
+    });
+
+    it("Refunds the escrow and vault rent to the relayer that paid it", async () => {
+      const [escrow] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("escrow"), maker.toBuffer(), seed14.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const escrowVault = getAssociatedTokenAddressSync(mintA, escrow, true);
+
+      await mintTo(provider.connection, provider.wallet.payer, mintA, makerAtaA, provider.wallet.payer, depositAmount);
+
+      const relayerLamportsBefore = await provider.connection.getBalance(relayer.publicKey);
+
+      await program.methods
+        .make(seed14, new anchor.BN(depositAmount), new anchor.BN(receiveAmount), expiresAt, null, { fixed: {} })
+        .accountsStrict({
+          maker: maker,
+          payer: relayer.publicKey,
+          mintA: mintA,
+          mintB: mintB,
+          makerAtaA: makerAtaA,
+          escrow,
+          vault: escrowVault,
+          protocol: protocolPda,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([relayer])
+        .rpc();
+
+      const escrowAccount = await program.account.escrow.fetch(escrow);
+      expect(escrowAccount.rentPayer.toBase58()).to.equal(relayer.publicKey.toBase58());
+      expect(escrowAccount.vaultRentPayer.toBase58()).to.equal(relayer.publicKey.toBase58());
+
+      const refundAccounts = {
+        maker: maker,
+        mintA: mintA,
+        makerAtaA: makerAtaA,
+        escrow,
+        vault: escrowVault,
+        rentPayer: relayer.publicKey,
+        vaultRentPayer: relayer.publicKey,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      };
+
+      // The maker can't route the relayer's rent to themselves
+      try {
+        await program.methods
+          .refund()
+          .accountsStrict({ ...refundAccounts, rentPayer: maker })
+          .rpc();
+        expect.fail("refund should have failed");
+      } catch (err) {
+        expect((err as anchor.AnchorError).error.errorCode.code).to.equal("InvalidRentPayer");
+      }
+
+      await program.methods.refund().accountsStrict(refundAccounts).rpc();
+
+      // The provider wallet paid the fees, so the relayer is made whole
+      const relayerLamports = await provider.connection.getBalance(relayer.publicKey);
+      expect(relayerLamports).to.equal(relayerLamportsBefore);
+    });
+  });
+
+  describe("signed intents", () => {
+    const seed15 = new anchor.BN(11001);
+    const [delegatePda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("delegate")],
+      program.programId
+    );
+
+    const intentMessage = (intent: any) =>
+      Buffer.concat([
+        Buffer.from("escrow-intent"),
+        program.programId.toBuffer(),
+        program.coder.types.encode("makeIntent", intent),
+      ]);
+
+    it("Lets a relayer post an offer the maker only signed off-chain", async () => {
+      const [escrow] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("escrow"), maker.toBuffer(), seed15.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const escrowVault = getAssociatedTokenAddressSync(mintA, escrow, true);
+
+      const nonce = new anchor.BN(1);
+      const [usedNonce] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("nonce"), maker.toBuffer(), nonce.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+
+      await mintTo(provider.connection, provider.wallet.payer, mintA, makerAtaA, provider.wallet.payer, depositAmount);
+      // One-off approval of the program's delegate on the maker's token account
+      await approve(provider.connection, provider.wallet.payer, makerAtaA, delegatePda, provider.wallet.payer, depositAmount);
+
+      const intent = {
+        maker,
+        seed: seed15,
+        mintA,
+        mintB,
+        deposit: new anchor.BN(depositAmount),
+        receive: new anchor.BN(receiveAmount),
+        expiresAt,
+        taker: null,
+        pricing: { fixed: {} },
+        nonce,
+        deadline: new anchor.BN(Math.floor(Date.now() / 1000) + 600),
+      };
+
+      const accounts = {
+        payer: taker.publicKey,
+        maker: maker,
+        mintA: mintA,
+        mintB: mintB,
+        makerAtaA: makerAtaA,
+        delegate: delegatePda,
+        escrow,
+        vault: escrowVault,
+        usedNonce,
+        instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        protocol: protocolPda,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      };
+
+      // A signature by anyone but the maker is rejected
+      try {
+        await program.methods
+          .makeWithIntent(intent)
+          .accountsStrict(accounts)
+          .preInstructions([
+            anchor.web3.Ed25519Program.createInstructionWithPrivateKey({
+              privateKey: taker.secretKey,
+              message: intentMessage(intent),
+            }),
+          ])
+          .signers([taker])
+          .rpc();
+        expect.fail("make should have failed");
+      } catch (err) {
+        expect((err as anchor.AnchorError).error.errorCode.code).to.equal("InvalidIntent");
+      }
+
+      // The taker relays the maker's signed intent and pays the rent
+      await program.methods
+        .makeWithIntent(intent)
+        .accountsStrict(accounts)
+        .preInstructions([
+          anchor.web3.Ed25519Program.createInstructionWithPrivateKey({
+            privateKey: provider.wallet.payer.secretKey,
+            message: intentMessage(intent),
+          }),
+        ])
+        .signers([taker])
+        .rpc();
+
+      const escrowAccount = await program.account.escrow.fetch(escrow);
+      expect(escrowAccount.maker.toBase58()).to.equal(maker.toBase58());
+      expect(escrowAccount.rentPayer.toBase58()).to.equal(taker.publicKey.toBase58());
+
+      const vaultBalance = (await provider.connection.getTokenAccountBalance(escrowVault)).value.uiAmount;
+      expect(vaultBalance).to.equal(depositAmount);
+
+      // The maker can still refund it; the rent goes back to the relayer
+      await program.methods
+        .refund()
+        .accountsStrict({
+          maker: maker,
+          mintA: mintA,
+          makerAtaA: makerAtaA,
+          escrow,
+          vault: escrowVault,
+          rentPayer: taker.publicKey,
+          vaultRentPayer: taker.publicKey,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      // The spent nonce keeps the same intent from being replayed
+      try {
+        await program.methods
+          .makeWithIntent(intent)
+          .accountsStrict(accounts)
+          .preInstructions([
+            anchor.web3.Ed25519Program.createInstructionWithPrivateKey({
+              privateKey: provider.wallet.payer.secretKey,
+              message: intentMessage(intent),
+            }),
+          ])
+          .signers([taker])
+          .rpc();
+        expect.fail("replay should have failed");
+      } catch (err) {
+        expect((err as Error).message).to.not.equal("replay should have failed");
+      }
+
+      // The nonce marker can't be closed while the intent could still be submitted
+      try {
+        await program.methods
+          .closeNonce()
+          .accountsStrict({ cranker: taker.publicKey, payer: taker.publicKey, usedNonce })
+          .signers([taker])
+          .rpc();
+        expect.fail("close should have failed");
+      } catch (err) {
+        expect((err as anchor.AnchorError).error.errorCode.code).to.equal("TimeoutNotReached");
+      }
+    });
+
+    it("Returns a spent nonce's rent to the relayer once the deadline has passed", async () => {
+      const seed = new anchor.BN(11002);
+      const [escrow] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("escrow"), maker.toBuffer(), seed.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+
+      const nonce = new anchor.BN(2);
+      const [usedNonce] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("nonce"), maker.toBuffer(), nonce.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+
+      await mintTo(provider.connection, provider.wallet.payer, mintA, makerAtaA, provider.wallet.payer, depositAmount);
+      await approve(provider.connection, provider.wallet.payer, makerAtaA, delegatePda, provider.wallet.payer, depositAmount);
+
+      const intent = {
+        maker,
+        seed,
+        mintA,
+        mintB,
+        deposit: new anchor.BN(depositAmount),
+        receive: new anchor.BN(receiveAmount),
+        expiresAt,
+        taker: null,
+        pricing: { fixed: {} },
+        nonce,
+        deadline: new anchor.BN(Math.floor(Date.now() / 1000) + 3),
+      };
+
+      await program.methods
+        .makeWithIntent(intent)
+        .accountsStrict({
+          payer: taker.publicKey,
+          maker: maker,
+          mintA: mintA,
+          mintB: mintB,
+          makerAtaA: makerAtaA,
+          delegate: delegatePda,
+          escrow,
+          vault: getAssociatedTokenAddressSync(mintA, escrow, true),
+          usedNonce,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          protocol: protocolPda,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .preInstructions([
+          anchor.web3.Ed25519Program.createInstructionWithPrivateKey({
+            privateKey: provider.wallet.payer.secretKey,
+            message: intentMessage(intent),
+          }),
+        ])
+        .signers([taker])
+        .rpc();
+
+      const nonceRent = (await provider.connection.getAccountInfo(usedNonce)).lamports;
+      await new Promise(resolve => setTimeout(resolve, 5000));
+
+      // Anyone can crank it; the rent goes to the relayer
+      const relayerBalanceBefore = await provider.connection.getBalance(taker.publicKey);
+      await program.methods
+        .closeNonce()
+        .accountsStrict({ cranker: maker, payer: taker.publicKey, usedNonce })
+        .rpc();
+
+      expect(await provider.connection.getAccountInfo(usedNonce)).to.be.null;
+      expect(await provider.connection.getBalance(taker.publicKey)).to.equal(relayerBalanceBefore + nonceRent);
+    });
+  });
+
+  describe("core assets", () => {
+    // Loaded into the local validator by the clone in Anchor.toml.
+    const MPL_CORE_ID = new anchor.web3.PublicKey("CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d");
+    const price = 50;
+    const bidDeposit = 40;
+
+    const collection = anchor.web3.Keypair.generate();
+    const otherCollection = anchor.web3.Keypair.generate();
+
+    // Core instructions are built by hand, like the program's TransferV1 CPI,
+    // so the tests need no Metaplex client. Optional accounts that are left
+    // out are passed as the Core program id.
+    const borshString = (value: string) => {
+      const bytes = Buffer.from(value);
+      const len = Buffer.alloc(4);
+      len.writeUInt32LE(bytes.length);
+      return Buffer.concat([len, bytes]);
+    };
+    const omitted = { pubkey: MPL_CORE_ID, isSigner: false, isWritable: false };
+
+    // CreateCollectionV1 { name, uri, plugins: None }, with the maker as update authority
+    const createCollection = async (collection: anchor.web3.Keypair) => {
+      const ix = new anchor.web3.TransactionInstruction({
+        programId: MPL_CORE_ID,
+        keys: [
+          { pubkey: collection.publicKey, isSigner: true, isWritable: true },
+          omitted,
+          { pubkey: maker, isSigner: true, isWritable: true },
+          { pubkey: anchor.web3.SystemProgram.programId, isSigner: false, isWritable: false },
+        ],
+        data: Buffer.concat([Buffer.from([1]), borshString("Escrow Collection"), borshString(""), Buffer.from([0])]),
+      });
+      await provider.sendAndConfirm(new anchor.web3.Transaction().add(ix), [collection]);
+    };
+
+    // CreateV1 { data_state: AccountState, name, uri, plugins: None }
+    const createAsset = async (owner: anchor.web3.PublicKey, collection?: anchor.web3.PublicKey) => {
+      const asset = anchor.web3.Keypair.generate();
+      const ix = new anchor.web3.TransactionInstruction({
+        programId: MPL_CORE_ID,
+        keys: [
+          { pubkey: asset.publicKey, isSigner: true, isWritable: true },
+          collection ? { pubkey: collection, isSigner: false, isWritable: true } : omitted,
+          omitted,
+          { pubkey: maker, isSigner: true, isWritable: true },
+          { pubkey: owner, isSigner: false, isWritable: false },
+          omitted,
+          { pubkey: anchor.web3.SystemProgram.programId, isSigner: false, isWritable: false },
+          omitted,
+        ],
+        data: Buffer.concat([Buffer.from([0, 0]), borshString("Escrow Asset"), borshString(""), Buffer.from([0])]),
+      });
+      await provider.sendAndConfirm(new anchor.web3.Transaction().add(ix), [asset]);
+      return asset.publicKey;
+    };
+
+    // AssetV1 starts with its key byte followed by the owner
+    const assetOwner = async (asset: anchor.web3.PublicKey) => {
+      const info = await provider.connection.getAccountInfo(asset);
+      return new anchor.web3.PublicKey(info.data.subarray(1, 33));
+    };
+
+    const offerPda = (seed: anchor.BN) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("asset_offer"), maker.toBuffer(), seed.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      )[0];
+
+    const bidPda = (seed: anchor.BN) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("asset_bid"), maker.toBuffer(), seed.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      )[0];
+
+    const makeOffer = (seed: anchor.BN, asset: anchor.web3.PublicKey, collection: anchor.web3.PublicKey | null) =>
+      program.methods
+        .makeAssetOffer(seed, new anchor.BN(price), expiresAt)
+        .accountsStrict({
+          maker: maker,
+          asset,
+          collection,
+          mintB: mintB,
+          offer: offerPda(seed),
+          mplCoreProgram: MPL_CORE_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+    const makeBid = async (seed: anchor.BN, target: object) => {
+      const bid = bidPda(seed);
+      await mintTo(provider.connection, provider.wallet.payer, mintA, makerAtaA, provider.wallet.payer, bidDeposit);
+      await program.methods
+        .makeAssetBid(seed, new anchor.BN(bidDeposit), target as any, expiresAt)
+        .accountsStrict({
+          maker: maker,
+          mintA: mintA,
+          makerAtaA: makerAtaA,
+          bid,
+          vault: getAssociatedTokenAddressSync(mintA, bid, true),
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+      return bid;
+    };
+
+    const takeBid = (
+      bid: anchor.web3.PublicKey,
+      target: object,
+      asset: anchor.web3.PublicKey,
+      collection: anchor.web3.PublicKey | null
+    ) =>
+      program.methods
+        .takeAssetBid(new anchor.BN(bidDeposit), target as any)
+        .accountsStrict({
+          taker: taker.publicKey,
+          maker: maker,
+          asset,
+          collection,
+          mintA: mintA,
+          takerAtaA: takerAtaA,
+          bid,
+          vault: getAssociatedTokenAddressSync(mintA, bid, true),
+          mplCoreProgram: MPL_CORE_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([taker])
+        .rpc();
+
+    before(async () => {
+      await createCollection(collection);
+      await createCollection(otherCollection);
+    });
+
+    it("Sells a Core asset for tokens", async () => {
+      const seed = new anchor.BN(11001);
+      const offer = offerPda(seed);
+      const asset = await createAsset(maker);
+
+      await makeOffer(seed, asset, null);
+      expect((await assetOwner(asset)).toBase58()).to.equal(offer.toBase58());
+
+      await mintTo(provider.connection, taker, mintB, takerAtaB, taker, price);
+      const makerBalanceBBefore = (await provider.connection.getTokenAccountBalance(makerAtaB)).value.uiAmount;
+
+      const takeOffer = (expectedReceive: number) =>
+        program.methods
+          .takeAssetOffer(new anchor.BN(expectedReceive))
+          .accountsStrict({
+            taker: taker.publicKey,
+            maker: maker,
+            asset,
+            collection: null,
+            mintB: mintB,
+            takerAtaB: takerAtaB,
+            makerAtaB: makerAtaB,
+            offer,
+            mplCoreProgram: MPL_CORE_ID,
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([taker])
+          .rpc();
+
+      // A taker who saw a cheaper offer is rejected
+      try {
+        await takeOffer(price - 1);
+        expect.fail("take should have failed");
+      } catch (err) {
+        expect((err as anchor.AnchorError).error.errorCode.code).to.equal("TermsChanged");
+      }
+
+      await takeOffer(price);
+
+      expect((await assetOwner(asset)).toBase58()).to.equal(taker.publicKey.toBase58());
+      const makerBalanceB = (await provider.connection.getTokenAccountBalance(makerAtaB)).value.uiAmount;
+      expect(makerBalanceB - makerBalanceBBefore).to.equal(price);
+      expect(await provider.connection.getAccountInfo(offer)).to.be.null;
+    });
+
+    it("Refunds a collection asset offer to the maker", async () => {
+      const seed = new anchor.BN(11002);
+      const offer = offerPda(seed);
+      const asset = await createAsset(maker, collection.publicKey);
+
+      await makeOffer(seed, asset, collection.publicKey);
+      expect((await assetOwner(asset)).toBase58()).to.equal(offer.toBase58());
+
+      await program.methods
+        .refundAssetOffer()
+        .accountsStrict({
+          maker: maker,
+          asset,
+          collection: collection.publicKey,
+          offer,
+          mplCoreProgram: MPL_CORE_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      expect((await assetOwner(asset)).toBase58()).to.equal(maker.toBase58());
+      expect(await provider.connection.getAccountInfo(offer)).to.be.null;
+    });
+
+    it("Rejects an offer for an asset the maker does not own", async () => {
+      const asset = await createAsset(taker.publicKey);
+
+      try {
+        await makeOffer(new anchor.BN(11003), asset, null);
+        expect.fail("make should have failed");
+      } catch (err) {
+        expect((err as anchor.AnchorError).error.errorCode.code).to.equal("InvalidAsset");
+      }
+    });
+
+    it("Rejects an offer whose collection does not match the asset", async () => {
+      const asset = await createAsset(maker, collection.publicKey);
+
+      try {
+        await makeOffer(new anchor.BN(11004), asset, otherCollection.publicKey);
+        expect.fail("make should have failed");
+      } catch (err) {
+        expect((err as anchor.AnchorError).error.errorCode.code).to.equal("InvalidCollection");
+      }
+    });
+
+    it("Fills a bid for one specific asset and rejects any other", async () => {
+      const wanted = await createAsset(taker.publicKey);
+      const other = await createAsset(taker.publicKey);
+      const target = { asset: [wanted] };
+      const bid = await makeBid(new anchor.BN(11005), target);
+
+      try {
+        await takeBid(bid, target, other, null);
+        expect.fail("take should have failed");
+      } catch (err) {
+        expect((err as anchor.AnchorError).error.errorCode.code).to.equal("InvalidAsset");
+      }
+
+      // A taker who saw a bid for another asset is rejected
+      try {
+        await takeBid(bid, { asset: [other] }, wanted, null);
+        expect.fail("take should have failed");
+      } catch (err) {
+        expect((err as anchor.AnchorError).error.errorCode.code).to.equal("TermsChanged");
+      }
+
+      const takerBalanceABefore = (await provider.connection.getTokenAccountBalance(takerAtaA)).value.uiAmount;
+      await takeBid(bid, target, wanted, null);
+
+      expect((await assetOwner(wanted)).toBase58()).to.equal(maker.toBase58());
+      const takerBalanceA = (await provider.connection.getTokenAccountBalance(takerAtaA)).value.uiAmount;
+      expect(takerBalanceA - takerBalanceABefore).to.equal(bidDeposit);
+      expect(await provider.connection.getAccountInfo(bid)).to.be.null;
+    });
+
+    it("Fills a collection bid with any asset from that collection", async () => {
+      const inCollection = await createAsset(taker.publicKey, collection.publicKey);
+      const outsider = await createAsset(taker.publicKey, otherCollection.publicKey);
+      const target = { collection: [collection.publicKey] };
+      const bid = await makeBid(new anchor.BN(11006), target);
+
+      // An asset from another collection is not what the bid asked for
+      try {
+        await takeBid(bid, target, outsider, otherCollection.publicKey);
+        expect.fail("take should have failed");
+      } catch (err) {
+        expect((err as anchor.AnchorError).error.errorCode.code).to.equal("InvalidAsset");
+      }
+
+      // Nor can the taker pass the bid's collection alongside a foreign asset
+      try {
+        await takeBid(bid, target, outsider, collection.publicKey);
+        expect.fail("take should have failed");
+      } catch (err) {
+        expect((err as anchor.AnchorError).error.errorCode.code).to.equal("InvalidCollection");
+      }
+
+      await takeBid(bid, target, inCollection, collection.publicKey);
+
+      expect((await assetOwner(inCollection)).toBase58()).to.equal(maker.toBase58());
+      expect(await provider.connection.getAccountInfo(bid)).to.be.null;
+    });
+
+    it("Refunds an unfilled asset bid", async () => {
+      const bid = await makeBid(new anchor.BN(11007), { collection: [collection.publicKey] });
+      const makerBalanceABefore = (await provider.connection.getTokenAccountBalance(makerAtaA)).value.uiAmount;
+
+      await program.methods
+        .refundAssetBid()
+        .accountsStrict({
+          maker: maker,
+          mintA: mintA,
+          makerAtaA: makerAtaA,
+          bid,
+          vault: getAssociatedTokenAddressSync(mintA, bid, true),
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      const makerBalanceA = (await provider.connection.getTokenAccountBalance(makerAtaA)).value.uiAmount;
+      expect(makerBalanceA - makerBalanceABefore).to.equal(bidDeposit);
+      expect(await provider.connection.getAccountInfo(bid)).to.be.null;
+    });
+  });
+});